@@ -0,0 +1,435 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    Attribute, Data, DeriveInput, Field, Fields, GenericArgument, Ident, LitStr, PathArguments,
+    Token, Type,
+};
+
+/// The methods `#[endpoint(...)]` accepts, as named by `http::Method`.
+const METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS", "CONNECT", "PATCH", "TRACE",
+];
+
+/// The body codecs `#[body(...)]` accepts, and whether they are generic over
+/// the field type.
+const CODECS: &[(&str, &str, bool)] = &[
+    ("json", "Json", true),
+    ("form", "Form", true),
+    ("cbor", "Cbor", true),
+    ("msgpack", "MsgPack", true),
+    ("text", "Text", false),
+    ("binary", "Binary", false),
+];
+
+/// `#[endpoint(GET "/contacts/{id}")]`.
+struct EndpointAttr {
+    method: Ident,
+    template: LitStr,
+}
+
+impl Parse for EndpointAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let method: Ident = input.parse()?;
+        if !METHODS.contains(&method.to_string().as_str()) {
+            return Err(syn::Error::new(
+                method.span(),
+                format!("expected one of {}", METHODS.join(", ")),
+            ));
+        }
+        let template: LitStr = input.parse()?;
+        if !template.value().starts_with('/') {
+            return Err(syn::Error::new(
+                template.span(),
+                "the path template must start with `/`",
+            ));
+        }
+        Ok(EndpointAttr { method, template })
+    }
+}
+
+/// Where a field is read from.
+enum Source {
+    Path(String),
+    Query(String),
+    Header(String),
+    Cookie(String),
+    Body(Ident),
+}
+
+/// One extractor of the generated HList, with the value it yields.
+struct Input {
+    ty: TokenStream,
+    extractor: TokenStream,
+    /// The field holding the value, or `None` for path literals.
+    field: Option<Ident>,
+}
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &data.fields,
+                    "EndpointInput can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "EndpointInput can only be derived for structs",
+            ))
+        }
+    };
+    let endpoint = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("endpoint"))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                &input.ident,
+                "expected an attribute like `#[endpoint(GET \"/contacts/{id}\")]`",
+            )
+        })?
+        .parse_args::<EndpointAttr>()?;
+
+    let mut path_fields = Vec::new();
+    let mut other_fields = Vec::new();
+    for field in fields {
+        let source = source(field)?;
+        match source {
+            Source::Path(name) => path_fields.push((name, field)),
+            source => other_fields.push((source, field)),
+        }
+    }
+    if other_fields
+        .iter()
+        .filter(|(source, _)| matches!(source, Source::Body(_)))
+        .count()
+        > 1
+    {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "only one field can be read from the body",
+        ));
+    }
+
+    // Path segments come first, in the order of the template, so each
+    // extractor finds its segment; the other inputs follow in field order.
+    let mut inputs = Vec::new();
+    let template = endpoint.template.value();
+    for segment in template.split('/').filter(|segment| !segment.is_empty()) {
+        let name = match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(name) => name,
+            None => {
+                inputs.push(Input {
+                    ty: quote!(::tapi::path::PathLiteral),
+                    extractor: quote!(::tapi::path::path_literal(#segment)),
+                    field: None,
+                });
+                continue;
+            }
+        };
+        let index = path_fields
+            .iter()
+            .position(|(field_name, _)| field_name == name)
+            .ok_or_else(|| {
+                syn::Error::new(
+                    endpoint.template.span(),
+                    format!("no `#[path_capture]` field is named `{}`", name),
+                )
+            })?;
+        let (_, field) = path_fields.remove(index);
+        let ty = &field.ty;
+        inputs.push(Input {
+            ty: quote!(::tapi::path::PathCapture<#ty>),
+            extractor: quote!(::tapi::path::path_capture(#name)),
+            field: field.ident.clone(),
+        });
+    }
+    if let Some((name, field)) = path_fields.first() {
+        return Err(syn::Error::new_spanned(
+            field,
+            format!("`{{{}}}` is not part of the path template", name),
+        ));
+    }
+    for (source, field) in other_fields {
+        inputs.push(extractor(source, field)?);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let method = &endpoint.method;
+    let types = inputs.iter().map(|input| &input.ty);
+    let extractors = inputs.iter().map(|input| &input.extractor);
+    // Literals are checked by the extractors but carry no value, so only
+    // the fields appear in the extracted list.
+    let values: Vec<_> = inputs
+        .iter()
+        .filter_map(|input| input.field.as_ref())
+        .collect();
+    let field_idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+
+    Ok(quote! {
+        impl #impl_generics ::tapi::input::EndpointInput for #ident #ty_generics #where_clause {
+            type Inputs = ::tapi::__private::frunk::HList![#(#types),*];
+
+            fn method() -> ::tapi::__private::axum::http::Method {
+                ::tapi::__private::axum::http::Method::#method
+            }
+
+            fn inputs() -> Self::Inputs {
+                ::tapi::__private::frunk::hlist![#(#extractors),*]
+            }
+
+            fn from_inputs(
+                inputs: <Self::Inputs as ::tapi::extract::Extractable>::Output,
+            ) -> Self {
+                let ::tapi::__private::frunk::hlist_pat![#(#values),*] = inputs;
+                #ident { #(#field_idents),* }
+            }
+
+            fn into_inputs(self) -> <Self::Inputs as ::tapi::extract::Extractable>::Output {
+                let #ident { #(#field_idents),* } = self;
+                ::tapi::__private::frunk::hlist![#(#values),*]
+            }
+        }
+    })
+}
+
+/// Reads the one source attribute of a field. Names default to the field
+/// name, with `_` written as `-` for headers.
+fn source(field: &Field) -> syn::Result<Source> {
+    let ident = field.ident.as_ref().expect("named fields have idents");
+    let field_name = ident.to_string().trim_start_matches("r#").to_string();
+    let mut found = None;
+    for attr in &field.attrs {
+        let source = if attr.path().is_ident("path_capture") {
+            Source::Path(name(attr)?.unwrap_or_else(|| field_name.clone()))
+        } else if attr.path().is_ident("query") {
+            Source::Query(name(attr)?.unwrap_or_else(|| field_name.clone()))
+        } else if attr.path().is_ident("header") {
+            Source::Header(name(attr)?.unwrap_or_else(|| field_name.replace('_', "-")))
+        } else if attr.path().is_ident("cookie") {
+            Source::Cookie(name(attr)?.unwrap_or_else(|| field_name.clone()))
+        } else if attr.path().is_ident("body") {
+            Source::Body(codec(attr)?)
+        } else {
+            continue;
+        };
+        if found.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "a field can only be read from one part of the request",
+            ));
+        }
+        found = Some(source);
+    }
+    found.ok_or_else(|| {
+        syn::Error::new_spanned(
+            field,
+            "expected one of `#[path_capture]`, `#[query]`, `#[header]`, `#[cookie]` or `#[body]`",
+        )
+    })
+}
+
+/// Parses the name of `#[query]`, `#[query("page")]` or
+/// `#[query(name = "page")]`.
+fn name(attr: &Attribute) -> syn::Result<Option<String>> {
+    if matches!(attr.meta, syn::Meta::Path(_)) {
+        return Ok(None);
+    }
+    attr.parse_args_with(|input: ParseStream| {
+        if input.peek(LitStr) {
+            return Ok(Some(input.parse::<LitStr>()?.value()));
+        }
+        let key: Ident = input.parse()?;
+        if key != "name" {
+            return Err(syn::Error::new(key.span(), "expected `name = \"...\"`"));
+        }
+        input.parse::<Token![=]>()?;
+        Ok(Some(input.parse::<LitStr>()?.value()))
+    })
+    .map(|name| name.filter(|name| !name.is_empty()))
+}
+
+/// Parses the codec of `#[body(json)]`; a bare `#[body]` is JSON.
+fn codec(attr: &Attribute) -> syn::Result<Ident> {
+    if matches!(attr.meta, syn::Meta::Path(_)) {
+        return Ok(Ident::new("json", Span::call_site()));
+    }
+    let codec: Ident = attr.parse_args()?;
+    if !CODECS.iter().any(|(name, _, _)| codec == name) {
+        let names: Vec<_> = CODECS.iter().map(|(name, _, _)| *name).collect();
+        return Err(syn::Error::new(
+            codec.span(),
+            format!("expected one of {}", names.join(", ")),
+        ));
+    }
+    Ok(codec)
+}
+
+fn extractor(source: Source, field: &Field) -> syn::Result<Input> {
+    let ty = &field.ty;
+    let (ty, extractor) = match source {
+        Source::Path(_) => unreachable!("path fields are placed by the template"),
+        Source::Query(name) => match (wrapped(ty, "Option"), wrapped(ty, "Vec")) {
+            (Some(inner), _) => (
+                quote!(::tapi::query::OptionalQueryExtractor<#inner>),
+                quote!(::tapi::query::optional_query(#name)),
+            ),
+            (_, Some(inner)) => (
+                quote!(::tapi::query::RepeatedQueryExtractor<#inner>),
+                quote!(::tapi::query::repeated_query(#name)),
+            ),
+            _ => (
+                quote!(::tapi::query::QueryExtractor<#ty>),
+                quote!(::tapi::query::query(#name)),
+            ),
+        },
+        Source::Header(name) => match (wrapped(ty, "Option"), wrapped(ty, "Vec")) {
+            (Some(inner), _) => (
+                quote!(::tapi::header::OptionalHeaderExtractor<#inner>),
+                quote!(::tapi::header::optional_header(#name)),
+            ),
+            (_, Some(inner)) => (
+                quote!(::tapi::header::RepeatedHeaderExtractor<#inner>),
+                quote!(::tapi::header::repeated_header(#name)),
+            ),
+            _ => (
+                quote!(::tapi::header::HeaderExtractor<#ty>),
+                quote!(::tapi::header::header(#name)),
+            ),
+        },
+        Source::Cookie(name) => (
+            quote!(::tapi::cookie::CookieExtractor<#ty>),
+            quote!(::tapi::cookie::cookie(#name)),
+        ),
+        Source::Body(codec) => {
+            let (_, codec_type, generic) = CODECS
+                .iter()
+                .find(|(name, _, _)| codec == name)
+                .expect("codecs are checked when parsed");
+            let codec_type = Ident::new(codec_type, codec.span());
+            let codec_type = if *generic {
+                quote!(::tapi::codec::#codec_type<#ty>)
+            } else {
+                quote!(::tapi::codec::#codec_type)
+            };
+            (
+                quote!(::tapi::extract::BodyExtractor<#codec_type>),
+                quote!(::tapi::extract::body()),
+            )
+        }
+    };
+    Ok(Input {
+        ty,
+        extractor,
+        field: field.ident.clone(),
+    })
+}
+
+/// Returns `T` if `ty` is written as `wrapper<T>`, e.g. `Option<T>`.
+fn wrapped<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn error(input: DeriveInput) -> String {
+        derive(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn path_templates_and_fields_must_match() {
+        let missing = error(parse_quote! {
+            #[endpoint(GET "/contacts/{id}")]
+            struct GetContact {}
+        });
+        assert_eq!(missing, "no `#[path_capture]` field is named `id`");
+
+        let extra = error(parse_quote! {
+            #[endpoint(GET "/contacts")]
+            struct GetContact {
+                #[path_capture]
+                id: u64,
+            }
+        });
+        assert_eq!(extra, "`{id}` is not part of the path template");
+    }
+
+    #[test]
+    fn attributes_are_checked() {
+        let method = error(parse_quote! {
+            #[endpoint(FETCH "/contacts")]
+            struct GetContacts {}
+        });
+        assert!(method.starts_with("expected one of GET, POST"));
+
+        let template = error(parse_quote! {
+            #[endpoint(GET "contacts")]
+            struct GetContacts {}
+        });
+        assert_eq!(template, "the path template must start with `/`");
+
+        let bodies = error(parse_quote! {
+            #[endpoint(POST "/contacts")]
+            struct CreateContacts {
+                #[body]
+                first: String,
+                #[body(text)]
+                second: String,
+            }
+        });
+        assert_eq!(bodies, "only one field can be read from the body");
+
+        let sources = error(parse_quote! {
+            #[endpoint(GET "/contacts")]
+            struct GetContacts {
+                #[query]
+                #[header]
+                page: u32,
+            }
+        });
+        assert_eq!(
+            sources,
+            "a field can only be read from one part of the request"
+        );
+    }
+
+    #[test]
+    fn names_default_to_the_field_name() {
+        let output = derive(parse_quote! {
+            #[endpoint(GET "/contacts")]
+            struct GetContacts {
+                #[header]
+                request_id: Option<String>,
+                #[query]
+                r#type: String,
+            }
+        })
+        .unwrap()
+        .to_string();
+        assert!(output.contains(r#"optional_header ("request-id")"#));
+        assert!(output.contains(r#"query ("type")"#));
+    }
+}
@@ -0,0 +1,48 @@
+//! Derive macros for tapi.rs. The generated code refers to the main crate
+//! as `::tapi`, which the crate also names itself, and reaches its
+//! dependencies through `::tapi::__private`.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod endpoint_input;
+mod schema;
+mod serde_attrs;
+mod validate_attrs;
+
+/// Implements `Schema` for a struct or enum, following its serde attributes
+/// (`rename`, `rename_all`, `skip`, `flatten`, `default`, `tag`, `content`,
+/// `untagged`, `transparent`).
+///
+/// Named fields may declare validators, e.g. `#[validate(minimum = 18,
+/// maximum = 120)]` or `#[validate(pattern = "^[a-z]+$")]`, which body
+/// inputs check once decoded and which are exported into the schema. The
+/// type itself implements `Validate` without supporting any validator, so
+/// collections of it may still limit their number of items.
+#[proc_macro_derive(Schema, attributes(serde, validate))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    schema::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `EndpointInput` for a struct whose fields are the inputs of an
+/// endpoint, declared via `#[endpoint(GET "/contacts/{id}")]` and one of
+/// `#[path_capture]`, `#[query]`, `#[header]`, `#[cookie]` or `#[body(json)]`
+/// per field. Names default to the field's, with `_` written as `-` for
+/// headers, or are given, e.g. `#[header("x-request-id")]`.
+///
+/// Path captures are marked `#[path_capture]` rather than `#[path]`: a
+/// derive helper named `path` would clash with the built-in `#[path]`
+/// attribute for modules, and every use would fail as ambiguous (E0659).
+#[proc_macro_derive(
+    EndpointInput,
+    attributes(endpoint, path_capture, query, header, cookie, body)
+)]
+pub fn derive_endpoint_input(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    endpoint_input::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,474 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed, Member};
+
+use crate::serde_attrs::{RenameRule, SerdeAttrs};
+use crate::validate_attrs::validators;
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let serde = SerdeAttrs::parse(&input.attrs)?;
+    let definition = match &input.data {
+        Data::Struct(data) => struct_definition(&serde, &data.fields)?,
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                let variant_serde = SerdeAttrs::parse(&variant.attrs)?;
+                if variant_serde.skip {
+                    continue;
+                }
+                let name = variant_serde.rename.clone().unwrap_or_else(|| {
+                    let ident = variant.ident.to_string();
+                    match serde.rename_all {
+                        Some(rule) => rule.apply_to_variant(&ident),
+                        None => ident,
+                    }
+                });
+                variants.push((name, variant_serde, &variant.fields));
+            }
+            enum_definition(&serde, &variants)?
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "Schema cannot be derived for unions",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    for parameter in input.generics.type_params() {
+        let parameter = &parameter.ident;
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#parameter: ::tapi::schema::Schema));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generic types are inlined, since their component name would depend on
+    // the type arguments.
+    let name = if input.generics.type_params().next().is_none() {
+        let name = serde.rename.clone().unwrap_or_else(|| ident.to_string());
+        quote! {
+            fn name() -> ::std::option::Option<::std::string::String> {
+                ::std::option::Option::Some(#name.to_string())
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let FieldChecks {
+        statics,
+        validate,
+        compile,
+    } = field_checks(&input, &serde)?;
+
+    // The rules of the fields live in statics next to the impl, so their
+    // patterns are compiled once for all values.
+    Ok(quote! {
+        const _: () = {
+            #(#statics)*
+
+            impl #impl_generics ::tapi::schema::Schema for #ident #ty_generics #where_clause {
+                #name
+
+                fn definition(components: &mut ::tapi::openapi::Components) -> ::tapi::__private::serde_json::Value {
+                    #definition
+                }
+
+                fn validate_fields(&self) -> ::std::result::Result<(), ::std::string::String> {
+                    #validate
+                }
+
+                fn compile_fields(compiled: &mut ::std::collections::BTreeSet<&'static str>) {
+                    #compile
+                }
+            }
+
+            // Values of this type support no validators themselves, but
+            // collections of them may limit their number of items.
+            impl #impl_generics ::tapi::validate::Validate for #ident #ty_generics #where_clause {
+                fn supports(_: &::tapi::validate::Validator) -> bool {
+                    false
+                }
+
+                fn validate(
+                    &self,
+                    _: &::tapi::validate::Validator,
+                    _: ::std::option::Option<&::tapi::__private::regex::Regex>,
+                ) -> ::std::result::Result<(), ::std::string::String> {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        };
+    })
+}
+
+/// The name serde gives a named field.
+fn field_name(field: &Field, serde: &SerdeAttrs, rename_all: Option<RenameRule>) -> String {
+    let ident = field.ident.as_ref().map(|ident| ident.to_string());
+    let ident = ident.unwrap_or_default();
+    let ident = ident.trim_start_matches("r#");
+    serde.rename.clone().unwrap_or_else(|| match rename_all {
+        Some(rule) => rule.apply_to_field(ident),
+        None => ident.to_string(),
+    })
+}
+
+/// The bodies of `validate_fields` and `compile_fields`, and the statics
+/// holding the `#[validate(...)]` rules of the fields.
+struct FieldChecks {
+    statics: Vec<TokenStream>,
+    validate: TokenStream,
+    compile: TokenStream,
+}
+
+/// Checks the rules of every field and the fields of the values they hold,
+/// with one match arm for the struct or per variant.
+fn field_checks(input: &DeriveInput, serde: &SerdeAttrs) -> syn::Result<FieldChecks> {
+    let mut shapes = Vec::new();
+    match &input.data {
+        Data::Struct(data) => shapes.push((quote!(Self), &data.fields, serde.rename_all)),
+        Data::Enum(data) => {
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let variant_serde = SerdeAttrs::parse(&variant.attrs)?;
+                shapes.push((
+                    quote!(Self::#ident),
+                    &variant.fields,
+                    variant_serde.rename_all,
+                ));
+            }
+        }
+        Data::Union(_) => {}
+    }
+
+    let mut statics = Vec::new();
+    let mut arms = Vec::new();
+    let mut compiles = Vec::new();
+    for (path, fields, rename_all) in shapes {
+        let mut patterns = Vec::new();
+        let mut checks = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            let field_serde = SerdeAttrs::parse(&field.attrs)?;
+            let validators = validators(&field.attrs)?;
+            let rejected = if field_serde.skip {
+                Some("skipped fields are not validated")
+            } else if field.ident.is_none() {
+                Some("`#[validate]` is only supported on named fields")
+            } else if serde.transparent {
+                Some("`#[validate]` is not supported on transparent types")
+            } else if field_serde.flatten {
+                Some("`#[validate]` cannot be combined with `#[serde(flatten)]`")
+            } else {
+                None
+            };
+            if let (Some(message), false) = (rejected, validators.is_empty()) {
+                return Err(syn::Error::new_spanned(field, message));
+            }
+            if field_serde.skip {
+                continue;
+            }
+
+            let ty = &field.ty;
+            let binding = format_ident!("field{}", index);
+            let name = match field.ident {
+                Some(_) => field_name(field, &field_serde, rename_all),
+                None => index.to_string(),
+            };
+            if !validators.is_empty() {
+                let rules = format_ident!("RULES_{}", statics.len());
+                statics.push(quote! {
+                    static #rules: ::tapi::validate::Rules = ::tapi::validate::rules(&[#(#validators),*]);
+                });
+                checks.push(quote! {
+                    ::tapi::validate::Rules::check(&#rules, #binding)
+                        .map_err(|rule| ::std::format!("{}: {}", #name, rule))?;
+                });
+                compiles.push(quote!(::tapi::validate::Rules::compile::<#ty>(&#rules);));
+            }
+            // Flattened and transparent fields are written in place of this
+            // value, so their broken rules are reported without a prefix.
+            if field_serde.flatten || serde.transparent {
+                checks.push(quote!(::tapi::schema::Schema::validate_fields(#binding)?;));
+            } else {
+                checks.push(quote! {
+                    ::tapi::schema::Schema::validate_fields(#binding)
+                        .map_err(|rule| ::std::format!("{}.{}", #name, rule))?;
+                });
+            }
+            compiles.push(quote!(<#ty as ::tapi::schema::Schema>::compile_fields(compiled);));
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(index.into()),
+            };
+            patterns.push(quote!(#member: #binding));
+        }
+        arms.push(quote!(#path { #(#patterns,)* .. } => { #(#checks)* }));
+    }
+
+    let validate = if arms.is_empty() {
+        quote!(::std::result::Result::Ok(()))
+    } else {
+        quote! {
+            match self {
+                #(#arms)*
+            }
+            ::std::result::Result::Ok(())
+        }
+    };
+    let compile = quote! {
+        if !compiled.insert(::std::any::type_name::<Self>()) {
+            return;
+        }
+        #(#compiles)*
+    };
+    Ok(FieldChecks {
+        statics,
+        validate,
+        compile,
+    })
+}
+
+fn struct_definition(serde: &SerdeAttrs, fields: &Fields) -> syn::Result<TokenStream> {
+    if serde.transparent {
+        let field = fields
+            .iter()
+            .find(|field| !matches!(SerdeAttrs::parse(&field.attrs), Ok(attrs) if attrs.skip))
+            .ok_or_else(|| syn::Error::new_spanned(fields, "expected a field"))?;
+        let ty = &field.ty;
+        return Ok(quote!(<#ty as ::tapi::schema::Schema>::schema(components)));
+    }
+    match fields {
+        Fields::Named(fields) => object(fields, serde.rename_all, None),
+        Fields::Unnamed(fields) => Ok(tuple(fields)),
+        Fields::Unit => Ok(quote!(
+            ::tapi::__private::serde_json::json!({ "type": "null" })
+        )),
+    }
+}
+
+/// An object schema for named fields, optionally with a constant `tag`
+/// property first, as used by internally tagged enums.
+fn object(
+    fields: &FieldsNamed,
+    rename_all: Option<RenameRule>,
+    tag: Option<(&str, &str)>,
+) -> syn::Result<TokenStream> {
+    let mut properties = Vec::new();
+    let mut flattened = Vec::new();
+    if let Some((tag, value)) = tag {
+        properties.push(quote! {
+            properties.insert(#tag.to_string(), ::tapi::__private::serde_json::json!({ "const": #value }));
+            required.push(#tag.into());
+        });
+    }
+    for field in &fields.named {
+        let serde = SerdeAttrs::parse(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        let ty = &field.ty;
+        if serde.flatten {
+            flattened.push(quote! {
+                ::tapi::schema::flatten(
+                    &mut object,
+                    <#ty as ::tapi::schema::Schema>::definition(components),
+                );
+            });
+            continue;
+        }
+        let name = field_name(field, &serde, rename_all);
+        let validators = validators(&field.attrs)?;
+        if validators.is_empty() {
+            properties.push(quote! {
+                properties.insert(
+                    #name.to_string(),
+                    <#ty as ::tapi::schema::Schema>::schema(components),
+                );
+            });
+        } else {
+            properties.push(quote! {
+                properties.insert(#name.to_string(), {
+                    let mut schema = <#ty as ::tapi::schema::Schema>::schema(components);
+                    #(::tapi::validate::Validator::describe(&#validators, &mut schema);)*
+                    schema
+                });
+            });
+        }
+        // Fields with a serde default may always be left out.
+        if !serde.default {
+            properties.push(quote! {
+                if !<#ty as ::tapi::schema::Schema>::optional() {
+                    required.push(#name.into());
+                }
+            });
+        }
+    }
+    Ok(quote! {{
+        #[allow(unused_mut)]
+        let mut properties = ::tapi::__private::serde_json::Map::new();
+        #[allow(unused_mut)]
+        let mut required: ::std::vec::Vec<::tapi::__private::serde_json::Value> = ::std::vec::Vec::new();
+        #(#properties)*
+        #[allow(unused_mut)]
+        let mut object = ::tapi::schema::object(properties, required);
+        #(#flattened)*
+        object
+    }})
+}
+
+/// A fixed-length array schema for the fields of a tuple struct or variant;
+/// a single field is documented as the field itself, like serde writes it.
+fn tuple(fields: &FieldsUnnamed) -> TokenStream {
+    let types: Vec<_> = fields.unnamed.iter().map(|field| &field.ty).collect();
+    if let [ty] = types.as_slice() {
+        return quote!(<#ty as ::tapi::schema::Schema>::schema(components));
+    }
+    let length = types.len();
+    quote! {
+        ::tapi::__private::serde_json::json!({
+            "type": "array",
+            "prefixItems": [#((<#types as ::tapi::schema::Schema>::schema(components))),*],
+            "minItems": #length,
+            "maxItems": #length,
+        })
+    }
+}
+
+fn enum_definition(
+    serde: &SerdeAttrs,
+    variants: &[(String, SerdeAttrs, &Fields)],
+) -> syn::Result<TokenStream> {
+    let externally_tagged = serde.tag.is_none() && !serde.untagged;
+    if externally_tagged
+        && variants
+            .iter()
+            .all(|(_, _, fields)| matches!(fields, Fields::Unit))
+    {
+        let names = variants.iter().map(|(name, _, _)| name);
+        return Ok(quote!(
+            ::tapi::__private::serde_json::json!({ "type": "string", "enum": [#(#names),*] })
+        ));
+    }
+
+    let mut schemas = Vec::new();
+    for (name, variant, fields) in variants {
+        let content = match fields {
+            Fields::Named(fields) => Some(object(fields, variant.rename_all, None)?),
+            Fields::Unnamed(fields) => Some(tuple(fields)),
+            Fields::Unit => None,
+        };
+        if serde.untagged {
+            schemas.push(content.unwrap_or_else(|| {
+                quote!(::tapi::__private::serde_json::json!({ "type": "null" }))
+            }));
+            continue;
+        }
+        let schema = match (&serde.tag, &serde.content, content) {
+            (None, _, None) => quote!(::tapi::__private::serde_json::json!({ "const": #name })),
+            (None, _, Some(content)) => quote! {
+                ::tapi::__private::serde_json::json!({
+                    "type": "object",
+                    "properties": { #name: (#content) },
+                    "required": [#name],
+                })
+            },
+            (Some(tag), None, _) => match fields {
+                Fields::Named(fields) => object(
+                    fields,
+                    variant.rename_all,
+                    Some((tag.as_str(), name.as_str())),
+                )?,
+                Fields::Unit => quote! {
+                    ::tapi::__private::serde_json::json!({
+                        "type": "object",
+                        "properties": { #tag: { "const": #name } },
+                        "required": [#tag],
+                    })
+                },
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let content = tuple(fields);
+                    quote! {
+                        ::tapi::__private::serde_json::json!({
+                            "allOf": [
+                                {
+                                    "type": "object",
+                                    "properties": { #tag: { "const": #name } },
+                                    "required": [#tag],
+                                },
+                                (#content),
+                            ],
+                        })
+                    }
+                }
+                Fields::Unnamed(fields) => {
+                    return Err(syn::Error::new_spanned(
+                        fields,
+                        "internally tagged enums cannot hold tuple variants",
+                    ))
+                }
+            },
+            (Some(tag), Some(_), None) => quote! {
+                ::tapi::__private::serde_json::json!({
+                    "type": "object",
+                    "properties": { #tag: { "const": #name } },
+                    "required": [#tag],
+                })
+            },
+            (Some(tag), Some(content_name), Some(content)) => quote! {
+                ::tapi::__private::serde_json::json!({
+                    "type": "object",
+                    "properties": { #tag: { "const": #name }, #content_name: (#content) },
+                    "required": [#tag, #content_name],
+                })
+            },
+        };
+        schemas.push(schema);
+    }
+    Ok(quote!(
+        ::tapi::__private::serde_json::json!({ "oneOf": [#((#schemas)),*] })
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn error(input: DeriveInput) -> String {
+        derive(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn validators_are_only_accepted_where_they_are_checked() {
+        let unnamed = error(parse_quote! {
+            struct Age(#[validate(minimum = 18)] u8);
+        });
+        assert_eq!(unnamed, "`#[validate]` is only supported on named fields");
+
+        let flattened = error(parse_quote! {
+            struct Contact {
+                #[serde(flatten)]
+                #[validate(min_items = 1)]
+                address: Address,
+            }
+        });
+        assert_eq!(
+            flattened,
+            "`#[validate]` cannot be combined with `#[serde(flatten)]`"
+        );
+
+        let skipped = error(parse_quote! {
+            struct Contact {
+                #[serde(skip)]
+                #[validate(min_length = 1)]
+                secret: String,
+            }
+        });
+        assert_eq!(skipped, "skipped fields are not validated");
+    }
+}
@@ -0,0 +1,280 @@
+use syn::parse::ParseStream;
+use syn::{Attribute, LitStr, Token};
+
+/// The `#[serde(rename_all = "...")]` rules.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> syn::Result<Self> {
+        Ok(match rule.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return Err(syn::Error::new(rule.span(), "unknown rename rule")),
+        })
+    }
+
+    /// Renames a variant, written in PascalCase, like serde does.
+    pub fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::Pascal => variant.to_string(),
+            RenameRule::Lower => variant.to_ascii_lowercase(),
+            RenameRule::Upper => variant.to_ascii_uppercase(),
+            RenameRule::Camel => {
+                let mut characters = variant.chars();
+                match characters.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + characters.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::Snake => {
+                let mut snake = String::new();
+                for (i, character) in variant.char_indices() {
+                    if i > 0 && character.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(character.to_ascii_lowercase());
+                }
+                snake
+            }
+            RenameRule::ScreamingSnake => RenameRule::Snake
+                .apply_to_variant(variant)
+                .to_ascii_uppercase(),
+            RenameRule::Kebab => RenameRule::Snake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Renames a field, written in snake_case, like serde does.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for character in field.chars() {
+                    if character == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(character.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(character);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply_to_field(field);
+                let mut characters = pascal.chars();
+                match characters.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + characters.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// The serde attributes that change the shape of a type or field. Others
+/// are ignored.
+#[derive(Default)]
+pub struct SerdeAttrs {
+    pub rename: Option<String>,
+    pub rename_all: Option<RenameRule>,
+    pub tag: Option<String>,
+    pub content: Option<String>,
+    pub untagged: bool,
+    pub transparent: bool,
+    pub skip: bool,
+    pub flatten: bool,
+    pub default: bool,
+}
+
+impl SerdeAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut serde = SerdeAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    serde.rename = Some(serialized_name(meta.input, |input| {
+                        Ok(input.parse::<LitStr>()?.value())
+                    })?);
+                } else if meta.path.is_ident("rename_all") {
+                    serde.rename_all = Some(serialized_name(meta.input, |input| {
+                        RenameRule::parse(&input.parse()?)
+                    })?);
+                } else if meta.path.is_ident("tag") {
+                    serde.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("content") {
+                    serde.content = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("untagged") {
+                    serde.untagged = true;
+                } else if meta.path.is_ident("transparent") {
+                    serde.transparent = true;
+                } else if meta.path.is_ident("skip")
+                    || meta.path.is_ident("skip_serializing")
+                    || meta.path.is_ident("skip_deserializing")
+                {
+                    serde.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    serde.flatten = true;
+                } else if meta.path.is_ident("default") {
+                    serde.default = true;
+                    skip_value(meta.input)?;
+                } else {
+                    skip_value(meta.input)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(serde)
+    }
+}
+
+/// Parses `= value` or the `serialize = value` half of
+/// `(serialize = value, deserialize = value)`.
+fn serialized_name<T>(
+    input: ParseStream,
+    parse: impl Fn(ParseStream) -> syn::Result<T>,
+) -> syn::Result<T> {
+    if input.peek(Token![=]) {
+        input.parse::<Token![=]>()?;
+        return parse(input);
+    }
+    let content;
+    syn::parenthesized!(content in input);
+    let mut serialized = None;
+    while !content.is_empty() {
+        let key: syn::Ident = content.parse()?;
+        content.parse::<Token![=]>()?;
+        let value = parse(&content)?;
+        if key == "serialize" {
+            serialized = Some(value);
+        }
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    serialized.ok_or_else(|| content.error("expected `serialize = ...`"))
+}
+
+/// Skips the value of an attribute this derive does not use.
+fn skip_value(input: ParseStream) -> syn::Result<()> {
+    if input.peek(Token![=]) {
+        input.parse::<Token![=]>()?;
+        input.parse::<syn::Expr>()?;
+    } else if input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in input);
+        content.parse::<proc_macro2::TokenStream>()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+    use serde::Serialize;
+    use serde_json::Value;
+
+    use super::*;
+
+    const FIELDS: [&str; 3] = ["name", "first_name", "page_id2"];
+
+    const VARIANTS: [&str; 3] = ["A", "NotFound", "HttpError2"];
+
+    /// The field and variant names serde writes under `rename_all = $rule`.
+    macro_rules! serde_names {
+        ($rule:literal) => {{
+            #[derive(Serialize)]
+            #[serde(rename_all = $rule)]
+            struct Fields {
+                name: u8,
+                first_name: u8,
+                page_id2: u8,
+            }
+
+            #[derive(Serialize)]
+            #[serde(rename_all = $rule)]
+            enum Variants {
+                A,
+                NotFound,
+                HttpError2,
+            }
+
+            let fields = Fields {
+                name: 0,
+                first_name: 0,
+                page_id2: 0,
+            };
+            let fields = match serde_json::to_value(fields).unwrap() {
+                Value::Object(fields) => fields.keys().cloned().collect::<Vec<_>>(),
+                _ => unreachable!(),
+            };
+            let variants = [Variants::A, Variants::NotFound, Variants::HttpError2]
+                .iter()
+                .map(|variant| serde_json::to_value(variant).unwrap())
+                .map(|variant| variant.as_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+            ($rule, fields, variants)
+        }};
+    }
+
+    #[test]
+    fn rename_rules_match_serde() {
+        let rules = [
+            serde_names!("lowercase"),
+            serde_names!("UPPERCASE"),
+            serde_names!("PascalCase"),
+            serde_names!("camelCase"),
+            serde_names!("snake_case"),
+            serde_names!("SCREAMING_SNAKE_CASE"),
+            serde_names!("kebab-case"),
+            serde_names!("SCREAMING-KEBAB-CASE"),
+        ];
+        for (rule, serde_fields, serde_variants) in rules {
+            let rename = RenameRule::parse(&LitStr::new(rule, Span::call_site())).unwrap();
+            let mut fields: Vec<_> = FIELDS
+                .iter()
+                .map(|field| rename.apply_to_field(field))
+                .collect();
+            // serde_json sorts object keys.
+            fields.sort();
+            assert_eq!(fields, serde_fields, "fields under {}", rule);
+            let variants: Vec<_> = VARIANTS
+                .iter()
+                .map(|variant| rename.apply_to_variant(variant))
+                .collect();
+            assert_eq!(variants, serde_variants, "variants under {}", rule);
+        }
+    }
+
+    #[test]
+    fn unknown_rename_rules_are_rejected() {
+        let rule = LitStr::new("Title Case", Span::call_site());
+        assert!(RenameRule::parse(&rule).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr};
+
+/// The validators a field declares with `#[validate(...)]`, e.g.
+/// `#[validate(minimum = 18, maximum = 120)]`, as `Validator` expressions.
+pub fn validators(attrs: &[Attribute]) -> syn::Result<Vec<TokenStream>> {
+    let mut validators = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("validate")) {
+        attr.parse_nested_meta(|meta| {
+            let validator = if meta.path.is_ident("minimum") {
+                let minimum = value(&meta)?;
+                quote!(Minimum((#minimum) as f64))
+            } else if meta.path.is_ident("maximum") {
+                let maximum = value(&meta)?;
+                quote!(Maximum((#maximum) as f64))
+            } else if meta.path.is_ident("min_length") {
+                let minimum = value(&meta)?;
+                quote!(MinLength(#minimum))
+            } else if meta.path.is_ident("max_length") {
+                let maximum = value(&meta)?;
+                quote!(MaxLength(#maximum))
+            } else if meta.path.is_ident("pattern") {
+                let pattern = value(&meta)?;
+                quote!(Pattern(#pattern))
+            } else if meta.path.is_ident("one_of") {
+                let allowed = value(&meta)?;
+                quote!(OneOf(&#allowed))
+            } else if meta.path.is_ident("min_items") {
+                let minimum = value(&meta)?;
+                quote!(MinItems(#minimum))
+            } else if meta.path.is_ident("max_items") {
+                let maximum = value(&meta)?;
+                quote!(MaxItems(#maximum))
+            } else {
+                return Err(meta.error(
+                    "expected one of minimum, maximum, min_length, max_length, pattern, one_of, \
+                     min_items, max_items",
+                ));
+            };
+            validators.push(quote!(::tapi::validate::Validator::#validator));
+            Ok(())
+        })?;
+    }
+    Ok(validators)
+}
+
+fn value(meta: &ParseNestedMeta) -> syn::Result<Expr> {
+    meta.value()?.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn rules_become_validators() {
+        let attrs: Vec<Attribute> = vec![
+            parse_quote!(#[validate(minimum = 18, max_length = 3)]),
+            parse_quote!(#[serde(rename = "age")]),
+            parse_quote!(#[validate(one_of = ["a", "b"])]),
+        ];
+        let validators: Vec<String> = validators(&attrs)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            validators,
+            [
+                ":: tapi :: validate :: Validator :: Minimum ((18) as f64)",
+                ":: tapi :: validate :: Validator :: MaxLength (3)",
+                ":: tapi :: validate :: Validator :: OneOf (& [\"a\" , \"b\"])",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_rules_are_rejected() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[validate(email)])];
+        let error = validators(&attrs).unwrap_err().to_string();
+        assert!(error.starts_with("expected one of minimum, maximum"));
+    }
+}
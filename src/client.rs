@@ -0,0 +1,279 @@
+use std::fmt::{self, Debug, Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use axum::http::{Request, StatusCode};
+use frunk::hlist::HNil;
+use frunk::Generic;
+use hyper::body::Bytes;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client};
+
+use crate::decode::DecodeFailure;
+use crate::encode::EncodeFailure;
+use crate::endpoint::Endpoint;
+use crate::error_output::ErrorOutput;
+use crate::extract::Extractable;
+use crate::output::Encodable;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The future returned by the function of [`EndpointClient::into_fn`].
+pub type ClientFuture<T, E> = BoxFuture<'static, Result<T, ClientError<E>>>;
+
+/// Why calling an endpoint did not produce its output.
+#[derive(Debug)]
+pub enum ClientError<E> {
+    /// The inputs could not be written into a request.
+    Encode(EncodeFailure),
+    /// The request could not be sent or the response could not be read.
+    Transport(hyper::Error),
+    /// The response did not match the endpoint's outputs.
+    Decode(DecodeFailure),
+    /// The server responded with a status the endpoint does not declare,
+    /// e.g. a `400` for inputs it failed to decode.
+    UnexpectedStatus(StatusCode, String),
+    /// One of the business errors declared by the endpoint.
+    Application(E),
+}
+
+impl<E: Debug> Display for ClientError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Encode(failure) => write!(f, "{}", failure),
+            ClientError::Transport(error) => write!(f, "request failed: {}", error),
+            ClientError::Decode(failure) => write!(f, "{}", failure),
+            ClientError::UnexpectedStatus(status, body) => {
+                write!(f, "unexpected status {}: {}", status, body)
+            }
+            ClientError::Application(error) => write!(f, "{:?}", error),
+        }
+    }
+}
+
+impl<E: Debug> std::error::Error for ClientError<E> {}
+
+/// Calls an endpoint over HTTP: inputs are written into a request with the
+/// same extractors the server decodes them with, and the response is read
+/// back with the same outputs and error outputs.
+pub struct EndpointClient<In, Err, Out, Sec = HNil> {
+    endpoint: Arc<Endpoint<In, Err, Out, Sec>>,
+    base_url: String,
+    http: Client<HttpConnector>,
+}
+
+/// Creates a client for `endpoint` on the server at `base_url`, e.g.
+/// `http://localhost:3000`.
+pub fn client<In, Err, Out, Sec>(
+    endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+    base_url: impl Into<String>,
+) -> EndpointClient<In, Err, Out, Sec> {
+    EndpointClient {
+        endpoint: endpoint.into(),
+        base_url: base_url.into().trim_end_matches('/').to_string(),
+        http: Client::new(),
+    }
+}
+
+impl<In, Err, Out, Sec> EndpointClient<In, Err, Out, Sec>
+where
+    Sec: Extractable,
+    In: Extractable,
+    Err: ErrorOutput,
+    Out: Encodable,
+{
+    /// Calls an endpoint with security inputs, writing the credentials in
+    /// `security` into the request along with the other inputs.
+    pub async fn call_secure(
+        &self,
+        security: Sec::Output,
+        input: In::Output,
+    ) -> Result<Out::Input, ClientError<Err::Error>> {
+        let (mut parts, ()) = Request::new(()).into_parts();
+        parts.method = self.endpoint.method.clone();
+        let mut body = Bytes::new();
+        self.endpoint
+            .security_input
+            .encode(security, &mut parts, &mut body)
+            .map_err(ClientError::Encode)?;
+        self.endpoint
+            .input
+            .encode(input, &mut parts, &mut body)
+            .map_err(ClientError::Encode)?;
+        let uri = format!("{}{}", self.base_url, parts.uri);
+        parts.uri = uri
+            .parse()
+            .map_err(|error| ClientError::Encode(EncodeFailure::new("uri", error)))?;
+        let request = Request::from_parts(parts, Body::from(body));
+
+        let response = self
+            .http
+            .request(request)
+            .await
+            .map_err(ClientError::Transport)?;
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body)
+            .await
+            .map_err(ClientError::Transport)?;
+
+        if let Some(error) = self.endpoint.error_output.decode(parts.status, &body) {
+            return Err(ClientError::Application(
+                error.map_err(ClientError::Decode)?,
+            ));
+        }
+        if !parts.status.is_success() {
+            let message = String::from_utf8_lossy(&body).into_owned();
+            return Err(ClientError::UnexpectedStatus(parts.status, message));
+        }
+        self.endpoint
+            .output
+            .decode(&parts, &body)
+            .map_err(ClientError::Decode)
+    }
+}
+
+impl<In, Err, Out, Sec> EndpointClient<In, Err, Out, Sec>
+where
+    Sec: Extractable<Output = HNil>,
+    In: Extractable,
+    Err: ErrorOutput,
+    Out: Encodable,
+{
+    pub async fn call(&self, input: In::Output) -> Result<Out::Input, ClientError<Err::Error>> {
+        self.call_secure(HNil, input).await
+    }
+
+    /// Calls the endpoint with a struct whose generic representation is the
+    /// input HList.
+    pub async fn call_generic<S>(&self, input: S) -> Result<Out::Input, ClientError<Err::Error>>
+    where
+        S: Generic<Repr = In::Output>,
+    {
+        self.call(frunk::into_generic(input)).await
+    }
+}
+
+impl<In, Err, Out> EndpointClient<In, Err, Out>
+where
+    In: Extractable + Send + 'static,
+    Err: ErrorOutput + Send + Sync + 'static,
+    Err::Error: Send,
+    Out: Encodable + Send + Sync + 'static,
+    Out::Input: Send,
+{
+    /// Turns the client into a plain async function from the input HList to
+    /// the endpoint's result.
+    pub fn into_fn(self) -> impl Fn(In::Output) -> ClientFuture<Out::Input, Err::Error> {
+        let client = Arc::new(self);
+        move |input| {
+            let client = client.clone();
+            Box::pin(async move { client.call(input).await })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use axum::http::Method;
+    use axum::Router;
+    use frunk::{hlist, HList};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::codec::Json;
+    use crate::endpoint::endpoint;
+    use crate::error_output::{one_of_errors, OneOfErrors};
+    use crate::extract::empty_endpoint;
+    use crate::output::{empty_output, response_body, BodyOutput};
+    use crate::path::{path_capture, path_literal, PathCapture, PathLiteral};
+    use crate::schema::Schema;
+    use crate::server::RouterExt;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Schema)]
+    enum ApiError {
+        NotFound { id: u64 },
+    }
+
+    static CONTACTS: PathLiteral = path_literal("contacts");
+
+    static ID: PathCapture<u64> = path_capture("id");
+
+    static RAW_ID: PathCapture<String> = path_capture("id");
+
+    static NAME: BodyOutput<Json<String>> = response_body();
+
+    fn errors() -> OneOfErrors<ApiError> {
+        one_of_errors().variant::<Json<ApiError>>(StatusCode::NOT_FOUND, |_| true)
+    }
+
+    async fn get_contact(input: HList![u64]) -> Result<HList![String], ApiError> {
+        match input.head {
+            0 => Err(ApiError::NotFound { id: 0 }),
+            id => Ok(hlist![format!("contact {}", id)]),
+        }
+    }
+
+    /// Serves `GET /contacts/{id}` on a free local port.
+    async fn serve() -> String {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        let endpoint = endpoint(Method::GET, inputs)
+            .output(outputs)
+            .errors(errors());
+        let app = Router::new().endpoint(endpoint, get_contact);
+        let address = SocketAddr::from(([127, 0, 0, 1], 0));
+        let server = axum::Server::bind(&address).serve(app.into_make_service());
+        let base_url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        base_url
+    }
+
+    #[tokio::test]
+    async fn calls_decode_outputs_and_declared_errors() {
+        let base_url = serve().await;
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        let endpoint = endpoint(Method::GET, inputs)
+            .output(outputs)
+            .errors(errors());
+        let client = client(endpoint, base_url);
+
+        let contact = client.call(hlist![7]).await.unwrap();
+        assert_eq!(contact, hlist!["contact 7".to_string()]);
+        match client.call(hlist![0]).await {
+            Err(ClientError::Application(error)) => {
+                assert_eq!(error, ApiError::NotFound { id: 0 })
+            }
+            result => panic!("expected a NotFound error, got {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn undeclared_statuses_are_unexpected() {
+        let base_url = serve().await;
+        // A client whose id is a string, so it can send one the server
+        // cannot decode.
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&RAW_ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        let endpoint = endpoint(Method::GET, inputs)
+            .output(outputs)
+            .errors(errors());
+        let client = client(endpoint, base_url);
+
+        match client.call(hlist!["x".to_string()]).await {
+            Err(ClientError::UnexpectedStatus(status, _)) => {
+                assert_eq!(status, StatusCode::BAD_REQUEST)
+            }
+            result => panic!("expected an unexpected status, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+
+use hyper::body::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::openapi::Components;
+use crate::schema::Schema;
+
+pub type CodecError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Converts between a body value and its representation in one media type.
+pub trait BodyCodec {
+    type Value;
+
+    /// The media type of bodies in this format, e.g. `application/json`.
+    const MEDIA_TYPE: &'static str;
+
+    fn decode(body: &Bytes) -> Result<Self::Value, CodecError>;
+    fn encode(value: &Self::Value) -> Result<Bytes, CodecError>;
+
+    /// The JSON Schema documenting bodies in this format.
+    fn schema(components: &mut Components) -> Value;
+
+    /// Checks the field rules of a decoded value, see
+    /// [`Schema::validate_fields`].
+    fn validate(_value: &Self::Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Prepares [`BodyCodec::validate`] when an endpoint is built.
+    fn compile() {}
+}
+
+/// A JSON body, (de)serialized with serde_json.
+pub struct Json<T>(PhantomData<fn() -> T>);
+
+/// A UTF-8 plain text body.
+pub struct Text;
+
+/// A body passed through as raw bytes.
+pub struct Binary;
+
+/// An `application/x-www-form-urlencoded` body, (de)serialized with
+/// serde_urlencoded.
+pub struct Form<T>(PhantomData<fn() -> T>);
+
+impl<T> BodyCodec for Json<T>
+where
+    T: Serialize + DeserializeOwned + Schema,
+{
+    type Value = T;
+
+    const MEDIA_TYPE: &'static str = "application/json";
+
+    fn decode(body: &Bytes) -> Result<T, CodecError> {
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    fn encode(value: &T) -> Result<Bytes, CodecError> {
+        Ok(Bytes::from(serde_json::to_vec(value)?))
+    }
+
+    fn schema(components: &mut Components) -> Value {
+        T::schema(components)
+    }
+
+    fn validate(value: &T) -> Result<(), String> {
+        value.validate_fields()
+    }
+
+    fn compile() {
+        T::compile_fields(&mut BTreeSet::new())
+    }
+}
+
+impl BodyCodec for Text {
+    type Value = String;
+
+    const MEDIA_TYPE: &'static str = "text/plain; charset=utf-8";
+
+    fn decode(body: &Bytes) -> Result<String, CodecError> {
+        Ok(String::from_utf8(body.to_vec())?)
+    }
+
+    fn encode(value: &String) -> Result<Bytes, CodecError> {
+        Ok(Bytes::from(value.clone()))
+    }
+
+    fn schema(components: &mut Components) -> Value {
+        String::schema(components)
+    }
+}
+
+impl BodyCodec for Binary {
+    type Value = Bytes;
+
+    const MEDIA_TYPE: &'static str = "application/octet-stream";
+
+    fn decode(body: &Bytes) -> Result<Bytes, CodecError> {
+        Ok(body.clone())
+    }
+
+    fn encode(value: &Bytes) -> Result<Bytes, CodecError> {
+        Ok(value.clone())
+    }
+
+    fn schema(_: &mut Components) -> Value {
+        json!({ "type": "string", "format": "binary" })
+    }
+}
+
+impl<T> BodyCodec for Form<T>
+where
+    T: Serialize + DeserializeOwned + Schema,
+{
+    type Value = T;
+
+    const MEDIA_TYPE: &'static str = "application/x-www-form-urlencoded";
+
+    fn decode(body: &Bytes) -> Result<T, CodecError> {
+        Ok(serde_urlencoded::from_bytes(body)?)
+    }
+
+    fn encode(value: &T) -> Result<Bytes, CodecError> {
+        Ok(Bytes::from(serde_urlencoded::to_string(value)?))
+    }
+
+    fn schema(components: &mut Components) -> Value {
+        T::schema(components)
+    }
+
+    fn validate(value: &T) -> Result<(), String> {
+        value.validate_fields()
+    }
+
+    fn compile() {
+        T::compile_fields(&mut BTreeSet::new())
+    }
+}
+
+/// A CBOR body, (de)serialized with ciborium.
+#[cfg(feature = "cbor")]
+pub struct Cbor<T>(PhantomData<fn() -> T>);
+
+/// A MessagePack body, (de)serialized with rmp-serde.
+#[cfg(feature = "msgpack")]
+pub struct MsgPack<T>(PhantomData<fn() -> T>);
+
+#[cfg(feature = "cbor")]
+impl<T> BodyCodec for Cbor<T>
+where
+    T: Serialize + DeserializeOwned + Schema,
+{
+    type Value = T;
+
+    const MEDIA_TYPE: &'static str = "application/cbor";
+
+    fn decode(body: &Bytes) -> Result<T, CodecError> {
+        Ok(ciborium::from_reader(body.as_ref())?)
+    }
+
+    fn encode(value: &T) -> Result<Bytes, CodecError> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes)?;
+        Ok(Bytes::from(bytes))
+    }
+
+    fn schema(components: &mut Components) -> Value {
+        T::schema(components)
+    }
+
+    fn validate(value: &T) -> Result<(), String> {
+        value.validate_fields()
+    }
+
+    fn compile() {
+        T::compile_fields(&mut BTreeSet::new())
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<T> BodyCodec for MsgPack<T>
+where
+    T: Serialize + DeserializeOwned + Schema,
+{
+    type Value = T;
+
+    const MEDIA_TYPE: &'static str = "application/vnd.msgpack";
+
+    fn decode(body: &Bytes) -> Result<T, CodecError> {
+        Ok(rmp_serde::from_slice(body)?)
+    }
+
+    /// Structs are encoded as maps, so field names survive the round trip
+    /// like they do in JSON.
+    fn encode(value: &T) -> Result<Bytes, CodecError> {
+        Ok(Bytes::from(rmp_serde::to_vec_named(value)?))
+    }
+
+    fn schema(components: &mut Components) -> Value {
+        T::schema(components)
+    }
+
+    fn validate(value: &T) -> Result<(), String> {
+        value.validate_fields()
+    }
+
+    fn compile() {
+        T::compile_fields(&mut BTreeSet::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::schema::Schema;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Schema)]
+    struct Contact {
+        name: String,
+        age: u8,
+    }
+
+    fn contact() -> Contact {
+        Contact {
+            name: "Jane Doe".to_string(),
+            age: 42,
+        }
+    }
+
+    #[test]
+    fn structured_codecs_round_trip() {
+        let json = Json::<Contact>::encode(&contact()).unwrap();
+        assert_eq!(json, r#"{"name":"Jane Doe","age":42}"#);
+        assert_eq!(Json::<Contact>::decode(&json).unwrap(), contact());
+
+        let form = Form::<Contact>::encode(&contact()).unwrap();
+        assert_eq!(form, "name=Jane+Doe&age=42");
+        assert_eq!(Form::<Contact>::decode(&form).unwrap(), contact());
+
+        assert!(Json::<Contact>::decode(&Bytes::from_static(b"{}")).is_err());
+    }
+
+    #[test]
+    fn text_must_be_utf8() {
+        let text = Text::encode(&"héllo".to_string()).unwrap();
+        assert_eq!(Text::decode(&text).unwrap(), "héllo");
+        assert!(Text::decode(&Bytes::from_static(&[0xff])).is_err());
+
+        let bytes = Bytes::from_static(&[0xff, 0x00]);
+        assert_eq!(Binary::decode(&bytes).unwrap(), bytes);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_round_trips() {
+        let cbor = Cbor::<Contact>::encode(&contact()).unwrap();
+        assert_eq!(Cbor::<Contact>::decode(&cbor).unwrap(), contact());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_keeps_field_names() {
+        let msgpack = MsgPack::<Contact>::encode(&contact()).unwrap();
+        assert!(msgpack.windows(4).any(|window| window == b"name"));
+        assert_eq!(MsgPack::<Contact>::decode(&msgpack).unwrap(), contact());
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use axum::http::header::{HeaderValue, COOKIE};
+use axum::http::request::Parts;
+use hyper::body::Bytes;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::decode::{DecodeFailure, DecodeInput};
+use crate::encode::EncodeFailure;
+use crate::extract::Extractor;
+use crate::header::header_values;
+use crate::openapi::{Components, Operation, Parameter, ParameterLocation};
+use crate::schema::Schema;
+
+/// Characters percent-encoded when writing a cookie value.
+const COOKIE_VALUE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'%')
+    .add(b',')
+    .add(b';')
+    .add(b'\\');
+
+/// A required cookie from the `Cookie` header, parsed via [`FromStr`].
+#[derive(Clone)]
+pub struct CookieExtractor<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+pub const fn cookie<T>(name: &'static str) -> CookieExtractor<T> {
+    CookieExtractor {
+        name,
+        _output: PhantomData,
+    }
+}
+
+/// Returns the raw value of the cookie `name`, looking through every
+/// `Cookie` header of the request.
+pub fn cookie_value<'a>(request: &'a Parts, name: &str) -> Result<Option<&'a str>, DecodeFailure> {
+    let cookies = header_values(request, COOKIE.as_str())?;
+    Ok(cookies
+        .into_iter()
+        .flat_map(|header| header.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.trim_matches('"')))
+}
+
+/// Adds the cookie `name` to the `Cookie` header of a request being built.
+pub fn append_cookie(request: &mut Parts, name: &str, value: &str) -> Result<(), EncodeFailure> {
+    let pair = format!("{}={}", name, utf8_percent_encode(value, COOKIE_VALUE));
+    let cookies = match request.headers.get(COOKIE).map(HeaderValue::to_str) {
+        Some(Ok(existing)) => format!("{}; {}", existing, pair),
+        _ => pair,
+    };
+    let cookies = HeaderValue::from_str(&cookies)
+        .map_err(|error| EncodeFailure::new(format!("cookie `{}`", name), error))?;
+    request.headers.insert(COOKIE, cookies);
+    Ok(())
+}
+
+#[async_trait]
+impl<T> Extractor for CookieExtractor<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = T;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<T, DecodeFailure> {
+        let input = || DecodeInput::Cookie(self.name.to_string());
+        let value =
+            cookie_value(request, self.name)?.ok_or_else(|| DecodeFailure::missing(input()))?;
+        percent_decode_str(value)
+            .decode_utf8()
+            .map_err(|error| DecodeFailure::parse(input(), error))?
+            .parse::<T>()
+            .map_err(|error| DecodeFailure::parse(input(), error))
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Cookie(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        append_cookie(request, self.name, &value.to_string())
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Cookie,
+            true,
+            T::schema(components),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+
+    fn parts(cookies: &[&str]) -> Parts {
+        let mut request = Request::builder();
+        for cookie in cookies {
+            request = request.header(COOKIE, *cookie);
+        }
+        request.body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn values_are_found_across_cookie_headers() {
+        let request = parts(&["theme=dark; lang=\"en\"", "session=abc%20def"]);
+        assert_eq!(cookie_value(&request, "lang").unwrap(), Some("en"));
+        assert_eq!(
+            cookie_value(&request, "session").unwrap(),
+            Some("abc%20def")
+        );
+        assert_eq!(cookie_value(&request, "them").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn cookies_are_percent_decoded() {
+        let request = parts(&["session=abc%20def", "count=x"]);
+        let body = Bytes::new();
+
+        let session = Extractor::extract(&cookie::<String>("session"), &request, &body).await;
+        assert_eq!(session.unwrap(), "abc def");
+        let count = Extractor::extract(&cookie::<u32>("count"), &request, &body).await;
+        assert_eq!(
+            count.unwrap_err().input,
+            DecodeInput::Cookie("count".to_string())
+        );
+        let missing = Extractor::extract(&cookie::<String>("theme"), &request, &body).await;
+        assert_eq!(
+            missing.unwrap_err(),
+            DecodeFailure::missing(DecodeInput::Cookie("theme".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn appended_cookies_are_encoded_and_read_back() {
+        let mut request = parts(&["theme=dark"]);
+        append_cookie(&mut request, "session", "abc; def").unwrap();
+        assert_eq!(request.headers[COOKIE], "theme=dark; session=abc%3B%20def");
+
+        let session = cookie::<String>("session");
+        let value = Extractor::extract(&session, &request, &Bytes::new()).await;
+        assert_eq!(value.unwrap(), "abc; def");
+    }
+}
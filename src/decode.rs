@@ -0,0 +1,204 @@
+use std::fmt;
+
+use axum::http::StatusCode;
+
+/// The part of a request that failed to decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeInput {
+    /// A path segment, counted from 0 after the leading `/`.
+    PathSegment(usize),
+    /// The query parameter with the given name.
+    Query(String),
+    /// The query string as a whole.
+    QueryString,
+    /// The header with the given name.
+    Header(String),
+    /// The cookie with the given name.
+    Cookie(String),
+    /// The request body.
+    Body,
+    /// Several values converted into one, e.g. via
+    /// [`Extractable::try_map_inputs`](crate::extract::Extractable::try_map_inputs).
+    Mapped,
+}
+
+/// Why an input failed to decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeReason {
+    /// The input is not present in the request.
+    Missing,
+    /// The input is present but could not be parsed into the expected type.
+    Parse(String),
+    /// The input could not be deserialized into the expected type.
+    Deserialize(String),
+    /// The input does not match the expected literal value.
+    Mismatch(String),
+    /// The body has a media type none of its codecs can decode.
+    UnsupportedMediaType(String),
+    /// None of the media types the endpoint can respond with are acceptable.
+    NotAcceptable(Vec<String>),
+    /// The input decoded, but breaks one of its validation rules.
+    Invalid(String),
+}
+
+/// A structured error describing which input of a request could not be
+/// decoded, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeFailure {
+    pub input: DecodeInput,
+    pub reason: DecodeReason,
+}
+
+impl DecodeFailure {
+    pub fn new(input: DecodeInput, reason: DecodeReason) -> Self {
+        DecodeFailure { input, reason }
+    }
+
+    pub fn missing(input: DecodeInput) -> Self {
+        DecodeFailure::new(input, DecodeReason::Missing)
+    }
+
+    pub fn parse(input: DecodeInput, error: impl fmt::Display) -> Self {
+        DecodeFailure::new(input, DecodeReason::Parse(error.to_string()))
+    }
+
+    pub fn deserialize(input: DecodeInput, error: impl fmt::Display) -> Self {
+        DecodeFailure::new(input, DecodeReason::Deserialize(error.to_string()))
+    }
+
+    /// The status code a server should respond with when this failure occurs.
+    pub fn status(&self) -> StatusCode {
+        match self.reason {
+            DecodeReason::Mismatch(_) => StatusCode::NOT_FOUND,
+            DecodeReason::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            DecodeReason::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl fmt::Display for DecodeInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeInput::PathSegment(index) => write!(f, "path segment {}", index),
+            DecodeInput::Query(name) => write!(f, "query parameter `{}`", name),
+            DecodeInput::QueryString => write!(f, "query string"),
+            DecodeInput::Header(name) => write!(f, "header `{}`", name),
+            DecodeInput::Cookie(name) => write!(f, "cookie `{}`", name),
+            DecodeInput::Body => write!(f, "body"),
+            DecodeInput::Mapped => write!(f, "mapped value"),
+        }
+    }
+}
+
+impl fmt::Display for DecodeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            DecodeReason::Missing => write!(f, "{} is missing", self.input),
+            DecodeReason::Parse(error) => {
+                write!(f, "{} could not be parsed: {}", self.input, error)
+            }
+            DecodeReason::Deserialize(error) => {
+                write!(f, "{} could not be deserialized: {}", self.input, error)
+            }
+            DecodeReason::Mismatch(expected) => {
+                write!(f, "{} does not match `{}`", self.input, expected)
+            }
+            DecodeReason::UnsupportedMediaType(media_type) => {
+                write!(
+                    f,
+                    "{} has unsupported media type `{}`",
+                    self.input, media_type
+                )
+            }
+            DecodeReason::NotAcceptable(offered) => {
+                write!(f, "{} accepts none of {}", self.input, offered.join(", "))
+            }
+            DecodeReason::Invalid(rule) => write!(f, "{} is invalid: {}", self.input, rule),
+        }
+    }
+}
+
+impl std::error::Error for DecodeFailure {}
+
+/// All decode failures of a request, in the order the inputs were declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeFailures(pub Vec<DecodeFailure>);
+
+impl DecodeFailures {
+    pub fn iter(&self) -> std::slice::Iter<'_, DecodeFailure> {
+        self.0.iter()
+    }
+
+    /// The status code a server should respond with, taken from the first
+    /// failure.
+    pub fn status(&self) -> StatusCode {
+        self.0
+            .first()
+            .map_or(StatusCode::BAD_REQUEST, DecodeFailure::status)
+    }
+}
+
+impl From<DecodeFailure> for DecodeFailures {
+    fn from(failure: DecodeFailure) -> Self {
+        DecodeFailures(vec![failure])
+    }
+}
+
+impl IntoIterator for DecodeFailures {
+    type Item = DecodeFailure;
+    type IntoIter = std::vec::IntoIter<DecodeFailure>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl fmt::Display for DecodeFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, failure) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DecodeFailures {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_name_the_input_and_reason() {
+        let missing = DecodeFailure::missing(DecodeInput::Header("x-request-id".to_string()));
+        assert_eq!(missing.to_string(), "header `x-request-id` is missing");
+
+        let parse = DecodeFailure::parse(DecodeInput::PathSegment(1), "invalid digit");
+        assert_eq!(
+            parse.reason,
+            DecodeReason::Parse("invalid digit".to_string())
+        );
+        assert_eq!(
+            parse.to_string(),
+            "path segment 1 could not be parsed: invalid digit"
+        );
+        assert_eq!(parse.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn failures_are_listed_together() {
+        let failures = DecodeFailures(vec![
+            DecodeFailure::missing(DecodeInput::PathSegment(0)),
+            DecodeFailure::missing(DecodeInput::Body),
+        ]);
+        assert_eq!(
+            failures.to_string(),
+            "path segment 0 is missing; body is missing"
+        );
+        assert_eq!(failures.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::Path;
+use axum::http::header::{HeaderValue, CONTENT_TYPE, LOCATION};
+use axum::http::{Response, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use hyper::body::Bytes;
+use utoipa_swagger_ui::Config;
+
+use crate::openapi::OpenApi;
+use crate::server::failure_response;
+
+fn document_response(media_type: &'static str, document: Bytes) -> Response<Body> {
+    let mut response = Response::new(Body::from(document));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(media_type));
+    response
+}
+
+fn redirect(location: &str) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::PERMANENT_REDIRECT;
+    if let Ok(location) = HeaderValue::from_str(location) {
+        response.headers_mut().insert(LOCATION, location);
+    }
+    response
+}
+
+/// Serves a file of the Swagger UI bundle, which is embedded in the binary.
+fn swagger_ui(file: &str, config: Arc<Config<'static>>) -> Response<Body> {
+    match utoipa_swagger_ui::serve(file, config) {
+        Ok(Some(file)) => {
+            let mut response = Response::new(Body::from(file.bytes.into_owned()));
+            if let Ok(content_type) = HeaderValue::from_str(&file.content_type) {
+                response.headers_mut().insert(CONTENT_TYPE, content_type);
+            }
+            response
+        }
+        Ok(None) => failure_response(StatusCode::NOT_FOUND, "not found"),
+        Err(error) => failure_response(StatusCode::INTERNAL_SERVER_ERROR, error),
+    }
+}
+
+/// Routes serving `document` under `mount_path`, e.g. `/docs`:
+/// `/docs/openapi.json`, `/docs/openapi.yaml`, and Swagger UI at `/docs/ui`,
+/// which `/docs` redirects to.
+/// Leading and trailing slashes of the mount path are ignored. It must not be
+/// `/`, so the UI does not shadow endpoints, which panics.
+pub fn docs_routes(document: &OpenApi, mount_path: &str) -> Router {
+    let mount_path = mount_path.trim_matches('/');
+    assert!(
+        !mount_path.is_empty(),
+        "the documentation cannot be mounted at `/`"
+    );
+    let mount_path = format!("/{}", mount_path);
+    let json = Bytes::from(document.to_json());
+    let yaml = Bytes::from(document.to_yaml());
+    let config = Arc::new(Config::new([format!("{}/openapi.json", mount_path)]));
+    let index = format!("{}/ui/index.html", mount_path);
+
+    Router::new()
+        .route(
+            &format!("{}/openapi.json", mount_path),
+            get(move || {
+                let json = json.clone();
+                async move { document_response("application/json", json) }
+            }),
+        )
+        .route(
+            &format!("{}/openapi.yaml", mount_path),
+            get(move || {
+                let yaml = yaml.clone();
+                async move { document_response("application/yaml", yaml) }
+            }),
+        )
+        .route(
+            &mount_path,
+            get(move || {
+                let index = index.clone();
+                async move { redirect(&index) }
+            }),
+        )
+        .route(
+            &format!("{}/ui/*file", mount_path),
+            get(move |Path(file): Path<String>| {
+                let config = config.clone();
+                async move { swagger_ui(&file, config) }
+            }),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::openapi::openapi;
+
+    async fn get(uri: &str) -> Response<axum::body::BoxBody> {
+        let document = openapi("test", "1.0");
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        docs_routes(&document, "/docs/")
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    async fn body(response: Response<axum::body::BoxBody>) -> Bytes {
+        hyper::body::to_bytes(response.into_body()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn documents_are_served_as_json_and_yaml() {
+        let document = openapi("test", "1.0");
+        let response = get("/docs/openapi.json").await;
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        assert_eq!(body(response).await, document.to_json());
+        let response = get("/docs/openapi.yaml").await;
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/yaml");
+        assert_eq!(body(response).await, document.to_yaml());
+    }
+
+    #[tokio::test]
+    async fn the_mount_path_redirects_to_swagger_ui() {
+        let response = get("/docs").await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(response.headers()[LOCATION], "/docs/ui/index.html");
+
+        let response = get("/docs/ui/index.html").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html");
+        let response = get("/docs/ui/swagger-initializer.js").await;
+        let initializer = body(response).await;
+        let initializer = String::from_utf8_lossy(&initializer);
+        assert!(initializer.contains("/docs/openapi.json"));
+
+        let response = get("/docs/ui/missing.js").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    #[should_panic(expected = "the documentation cannot be mounted at `/`")]
+    fn the_root_mount_path_is_rejected() {
+        let _ = docs_routes(&openapi("test", "1.0"), "/");
+    }
+}
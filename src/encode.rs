@@ -0,0 +1,33 @@
+use std::fmt;
+
+use axum::http::StatusCode;
+
+/// A structured error describing which part of a request or response could
+/// not be encoded, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeFailure {
+    pub target: String,
+    pub error: String,
+}
+
+impl EncodeFailure {
+    pub fn new(target: impl fmt::Display, error: impl fmt::Display) -> Self {
+        EncodeFailure {
+            target: target.to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    /// The status code a server should respond with when this failure occurs.
+    pub fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl fmt::Display for EncodeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} could not be encoded: {}", self.target, self.error)
+    }
+}
+
+impl std::error::Error for EncodeFailure {}
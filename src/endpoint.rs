@@ -0,0 +1,344 @@
+use std::fmt::Display;
+use std::ops::Add;
+
+use axum::body::Body;
+use axum::http::request::Parts;
+use axum::http::{Method, Response, StatusCode};
+use frunk::hlist::{HCons, HNil};
+use frunk::Generic;
+
+use crate::encode::EncodeFailure;
+use crate::error_output::ErrorOutput;
+use crate::extract::{Extractable, Structured, Tupled};
+use crate::map::{Mapped, MappedInputs, TryMapped, TryMappedInputs};
+use crate::output::Encodable;
+
+/// The error output of an endpoint that declares no errors.
+#[derive(Clone)]
+pub struct NoErrors;
+
+/// The full description of an endpoint: its method, the inputs decoded from
+/// a request, and the outputs encoded into a response. Interpreters such as
+/// servers, clients and documentation generators all work from this value.
+///
+/// Security inputs, such as a bearer token, are declared apart from the
+/// other inputs so that servers can authenticate a request before decoding
+/// the rest of it.
+///
+/// Endpoints can be cloned and extended, so a base endpoint can hold what
+/// several endpoints share, e.g. a path prefix, security inputs and errors.
+#[derive(Clone)]
+pub struct Endpoint<In, Err, Out, Sec = HNil> {
+    pub method: Method,
+    pub security_input: Sec,
+    pub input: In,
+    pub error_output: Err,
+    pub output: Out,
+    /// The status of successful responses, unless an output sets one.
+    pub status: StatusCode,
+    /// Names the endpoint in documentation, e.g. as the OpenAPI operation id.
+    pub name: Option<String>,
+}
+
+/// Starts an endpoint with its inputs, which are prepared right away, so
+/// wrongly declared inputs fail here rather than on the first request. Build
+/// each endpoint once at startup and share it via [`Arc`](std::sync::Arc).
+pub fn endpoint<In: Extractable>(method: Method, input: In) -> Endpoint<In, NoErrors, HNil> {
+    input.compile();
+    Endpoint {
+        method,
+        security_input: HNil,
+        input,
+        error_output: NoErrors,
+        output: HNil,
+        status: StatusCode::OK,
+        name: None,
+    }
+}
+
+impl<In, Err, Out, Sec> Endpoint<In, Err, Out, Sec> {
+    /// Declares the security inputs, e.g. `bearer()`, preparing them like
+    /// [`endpoint`] does the other inputs.
+    pub fn security_input<S: Extractable>(self, security_input: S) -> Endpoint<In, Err, Out, S> {
+        security_input.compile();
+        Endpoint {
+            method: self.method,
+            security_input,
+            input: self.input,
+            error_output: self.error_output,
+            output: self.output,
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    pub fn output<O: Encodable>(self, output: O) -> Endpoint<In, Err, O, Sec> {
+        Endpoint {
+            method: self.method,
+            security_input: self.security_input,
+            input: self.input,
+            error_output: self.error_output,
+            output,
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    pub fn errors<E: ErrorOutput>(self, error_output: E) -> Endpoint<In, E, Out, Sec> {
+        Endpoint {
+            method: self.method,
+            security_input: self.security_input,
+            input: self.input,
+            error_output,
+            output: self.output,
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    pub fn status(self, status: StatusCode) -> Self {
+        Endpoint { status, ..self }
+    }
+
+    pub fn name(self, name: impl Into<String>) -> Self {
+        Endpoint {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+}
+
+impl<In: Extractable, Err, Out, Sec> Endpoint<In, Err, Out, Sec> {
+    /// Adds an input after the ones already declared, e.g.
+    /// `base.clone().input(path_literal("contacts")).input(path_capture::<u64>("id"))`.
+    pub fn input<E>(
+        self,
+        extractor: E,
+    ) -> Endpoint<<In as Add<HCons<E, HNil>>>::Output, Err, Out, Sec>
+    where
+        In: Add<HCons<E, HNil>>,
+        <In as Add<HCons<E, HNil>>>::Output: Extractable,
+    {
+        let input = self.input.with_extractor(extractor);
+        input.compile();
+        Endpoint {
+            method: self.method,
+            security_input: self.security_input,
+            input,
+            error_output: self.error_output,
+            output: self.output,
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    /// Hands the inputs to the server logic as a plain tuple instead of an
+    /// HList, see [`Extractable::tupled`].
+    pub fn tupled(self) -> Endpoint<Tupled<In>, Err, Out, Sec> {
+        self.replace_input(Extractable::tupled)
+    }
+
+    /// Hands the inputs to the server logic as the struct `S`, see
+    /// [`Extractable::structured`].
+    pub fn structured<S>(self) -> Endpoint<Structured<In, S>, Err, Out, Sec>
+    where
+        S: Generic<Repr = In::Output>,
+    {
+        self.replace_input(Extractable::structured)
+    }
+
+    /// Hands the inputs to the server logic as a domain type, see
+    /// [`Extractable::map_inputs`].
+    pub fn map_input<U>(
+        self,
+        decode: fn(In::Output) -> U,
+        encode: fn(U) -> In::Output,
+    ) -> Endpoint<MappedInputs<In, In::Output, U>, Err, Out, Sec> {
+        self.replace_input(|input| input.map_inputs(decode, encode))
+    }
+
+    /// Like [`Endpoint::map_input`], for conversions that can fail, see
+    /// [`Extractable::try_map_inputs`].
+    pub fn try_map_input<U, Er: Display>(
+        self,
+        decode: fn(In::Output) -> Result<U, Er>,
+        encode: fn(U) -> In::Output,
+    ) -> Endpoint<TryMappedInputs<In, In::Output, U, Er>, Err, Out, Sec> {
+        self.replace_input(|input| input.try_map_inputs(decode, encode))
+    }
+
+    fn replace_input<I>(self, replace: impl FnOnce(In) -> I) -> Endpoint<I, Err, Out, Sec> {
+        Endpoint {
+            method: self.method,
+            security_input: self.security_input,
+            input: replace(self.input),
+            error_output: self.error_output,
+            output: self.output,
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    /// Changes the method, e.g. of an endpoint extended from a shared base.
+    pub fn method(self, method: Method) -> Self {
+        Endpoint { method, ..self }
+    }
+
+    pub fn path_template(&self) -> String {
+        self.input.path_template()
+    }
+}
+
+impl<In, Err, Out: Encodable, Sec> Endpoint<In, Err, Out, Sec> {
+    /// Lets the server logic return a domain type instead of the outputs'
+    /// HList, e.g. a struct holding a header and a body.
+    pub fn map_output<U>(
+        self,
+        decode: fn(Out::Input) -> U,
+        encode: fn(U) -> Out::Input,
+    ) -> Endpoint<In, Err, Mapped<Out, Out::Input, U>, Sec> {
+        let output = Mapped::new(self.output, decode, encode);
+        Endpoint {
+            method: self.method,
+            security_input: self.security_input,
+            input: self.input,
+            error_output: self.error_output,
+            output,
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    /// Like [`Endpoint::map_output`], for conversions from the outputs' HList
+    /// that can fail, which clients report as decode failures.
+    pub fn try_map_output<U, Er: Display>(
+        self,
+        decode: fn(Out::Input) -> Result<U, Er>,
+        encode: fn(U) -> Out::Input,
+    ) -> Endpoint<In, Err, TryMapped<Out, Out::Input, U, Er>, Sec> {
+        let output = TryMapped::new(self.output, decode, encode);
+        Endpoint {
+            method: self.method,
+            security_input: self.security_input,
+            input: self.input,
+            error_output: self.error_output,
+            output,
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    /// Lets the server logic return the struct `S`, deriving frunk's
+    /// `Generic`, whose fields are in the order the outputs were declared.
+    pub fn map_output_to<S>(self) -> Endpoint<In, Err, Mapped<Out, Out::Input, S>, Sec>
+    where
+        S: Generic<Repr = Out::Input>,
+    {
+        self.map_output(frunk::from_generic, frunk::into_generic)
+    }
+
+    /// Encodes the successful result of the server logic into a response.
+    pub fn encode_output(
+        &self,
+        value: Out::Input,
+        request: &Parts,
+    ) -> Result<Response<Body>, EncodeFailure> {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = self.status;
+        self.output.encode(value, request, &mut response)?;
+        Ok(response)
+    }
+}
+
+impl<In, Err: ErrorOutput, Out: Encodable, Sec> Endpoint<In, Err, Out, Sec> {
+    /// Encodes the result of the server logic into a response, using the
+    /// error output for business errors.
+    pub fn encode_result(
+        &self,
+        result: Result<Out::Input, Err::Error>,
+        request: &Parts,
+    ) -> Result<Response<Body>, EncodeFailure> {
+        match result {
+            Ok(value) => self.encode_output(value, request),
+            Err(error) => self.error_output.encode(error, request),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+    use frunk::hlist;
+
+    use super::*;
+    use crate::codec::{BodyCodec, Text};
+    use crate::extract::empty_endpoint;
+    use crate::output::{empty_output, response_body, response_header, status_code};
+    use crate::path::{path_capture, path_literal};
+    use crate::query::query;
+    use crate::security::bearer;
+    use crate::validate::{validated, Validator};
+
+    #[tokio::test]
+    async fn outputs_are_encoded_into_one_response() {
+        let contacts = path_literal("contacts");
+        let id = path_capture::<u64>("id");
+        let request_id = response_header::<String>("x-request-id");
+        let body = response_body::<Text>();
+        let status = status_code();
+        let endpoint = endpoint(
+            Method::GET,
+            empty_endpoint()
+                .with_extractor(&contacts)
+                .with_extractor(&id),
+        )
+        .output(
+            empty_output()
+                .with_encoder(&request_id)
+                .with_encoder(&body)
+                .with_encoder(&status),
+        )
+        .status(StatusCode::CREATED);
+        assert_eq!(endpoint.path_template(), "/contacts/{id}");
+
+        let request = Request::builder().body(()).unwrap().into_parts().0;
+        let response = endpoint
+            .encode_output(
+                hlist!["7f3c".to_string(), "Jane".to_string(), StatusCode::ACCEPTED],
+                &request,
+            )
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(response.headers()["x-request-id"], "7f3c");
+        assert_eq!(response.headers()["content-type"], Text::MEDIA_TYPE);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "Jane");
+
+        let endpoint = endpoint.output(empty_output());
+        let response = endpoint.encode_output(HNil, &request).unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[test]
+    fn cloned_endpoints_are_extended_independently() {
+        let base = endpoint(Method::GET, empty_endpoint())
+            .input(path_literal("api"))
+            .security_input(bearer());
+        let list = base.clone().input(path_literal("contacts"));
+        let get = base
+            .input(path_literal("contacts"))
+            .input(path_capture::<u64>("id"))
+            .method(Method::DELETE);
+        assert_eq!(list.path_template(), "/api/contacts");
+        assert_eq!(list.method, Method::GET);
+        assert_eq!(get.path_template(), "/api/contacts/{id}");
+        assert_eq!(get.method, Method::DELETE);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid pattern")]
+    fn added_inputs_are_prepared() {
+        let name = validated(query::<String>("name"), &[Validator::Pattern("(")]);
+        endpoint(Method::GET, empty_endpoint()).input(name);
+    }
+}
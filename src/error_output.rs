@@ -0,0 +1,205 @@
+use std::convert::Infallible;
+
+use axum::body::Body;
+use axum::http::request::Parts;
+use axum::http::{Response, StatusCode};
+use hyper::body::Bytes;
+use serde_json::Value;
+
+use crate::codec::{BodyCodec, CodecError};
+use crate::decode::{DecodeFailure, DecodeInput};
+use crate::encode::EncodeFailure;
+use crate::endpoint::NoErrors;
+use crate::openapi::{Components, MediaType, Operation};
+use crate::output::set_body;
+
+/// This trait is used to map the business errors of an endpoint to and from
+/// responses.
+pub trait ErrorOutput {
+    type Error;
+
+    fn encode(&self, error: Self::Error, request: &Parts) -> Result<Response<Body>, EncodeFailure>;
+
+    /// Decodes an error from a response, or returns `None` when `status` is
+    /// not one of the declared error statuses.
+    fn decode(
+        &self,
+        status: StatusCode,
+        body: &Bytes,
+    ) -> Option<Result<Self::Error, DecodeFailure>>;
+
+    /// Documents the error responses.
+    fn describe(&self, operation: &mut Operation, components: &mut Components);
+}
+
+impl ErrorOutput for NoErrors {
+    type Error = Infallible;
+
+    fn encode(&self, error: Infallible, _: &Parts) -> Result<Response<Body>, EncodeFailure> {
+        match error {}
+    }
+
+    fn decode(&self, _: StatusCode, _: &Bytes) -> Option<Result<Infallible, DecodeFailure>> {
+        None
+    }
+
+    fn describe(&self, _: &mut Operation, _: &mut Components) {}
+}
+
+/// One variant of a [`OneOfErrors`]: the errors it `matches` are responded
+/// with `status` and a body in `media_type`.
+pub struct ErrorVariant<E> {
+    pub status: StatusCode,
+    pub media_type: &'static str,
+    pub matches: fn(&E) -> bool,
+    pub encode: fn(&E) -> Result<Bytes, CodecError>,
+    pub decode: fn(&Bytes) -> Result<E, CodecError>,
+    pub schema: fn(&mut Components) -> Value,
+}
+
+/// Maps each variant of an error enum to a status code and a body codec,
+/// e.g. `NotFound -> 404 JSON`, `Conflict -> 409 JSON`.
+pub struct OneOfErrors<E> {
+    variants: Vec<ErrorVariant<E>>,
+}
+
+impl<E> Clone for ErrorVariant<E> {
+    fn clone(&self) -> Self {
+        ErrorVariant { ..*self }
+    }
+}
+
+impl<E> Clone for OneOfErrors<E> {
+    fn clone(&self) -> Self {
+        OneOfErrors {
+            variants: self.variants.clone(),
+        }
+    }
+}
+
+pub fn one_of_errors<E>() -> OneOfErrors<E> {
+    OneOfErrors {
+        variants: Vec::new(),
+    }
+}
+
+impl<E> OneOfErrors<E> {
+    pub fn variant<C: BodyCodec<Value = E>>(
+        mut self,
+        status: StatusCode,
+        matches: fn(&E) -> bool,
+    ) -> Self {
+        self.variants.push(ErrorVariant {
+            status,
+            media_type: C::MEDIA_TYPE,
+            matches,
+            encode: C::encode,
+            decode: C::decode,
+            schema: C::schema,
+        });
+        self
+    }
+
+    pub fn variants(&self) -> &[ErrorVariant<E>] {
+        &self.variants
+    }
+}
+
+impl<E> ErrorOutput for OneOfErrors<E> {
+    type Error = E;
+
+    fn encode(&self, error: E, _request: &Parts) -> Result<Response<Body>, EncodeFailure> {
+        let variant = self
+            .variants
+            .iter()
+            .find(|variant| (variant.matches)(&error))
+            .ok_or_else(|| EncodeFailure::new("error", "no error variant matches"))?;
+        let body = (variant.encode)(&error).map_err(|error| EncodeFailure::new("error", error))?;
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = variant.status;
+        set_body(&mut response, variant.media_type, body);
+        Ok(response)
+    }
+
+    fn decode(&self, status: StatusCode, body: &Bytes) -> Option<Result<E, DecodeFailure>> {
+        let variant = self
+            .variants
+            .iter()
+            .find(|variant| variant.status == status)?;
+        Some(
+            (variant.decode)(body)
+                .map_err(|error| DecodeFailure::deserialize(DecodeInput::Body, error)),
+        )
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        for variant in &self.variants {
+            operation.response(variant.status).content.insert(
+                variant.media_type.to_string(),
+                MediaType {
+                    schema: (variant.schema)(components),
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::codec::Json;
+    use crate::schema::Schema;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Schema)]
+    enum ApiError {
+        NotFound(u64),
+        Conflict(String),
+    }
+
+    fn errors() -> OneOfErrors<ApiError> {
+        one_of_errors()
+            .variant::<Json<ApiError>>(StatusCode::NOT_FOUND, |error| {
+                matches!(error, ApiError::NotFound(_))
+            })
+            .variant::<Json<ApiError>>(StatusCode::CONFLICT, |error| {
+                matches!(error, ApiError::Conflict(_))
+            })
+    }
+
+    #[tokio::test]
+    async fn variants_round_trip_through_their_status() {
+        let request = Request::builder().body(()).unwrap().into_parts().0;
+        let response = errors()
+            .encode(ApiError::Conflict("taken".to_string()), &request)
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers()["content-type"], "application/json");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        let decoded = errors().decode(StatusCode::CONFLICT, &body);
+        assert_eq!(
+            decoded.unwrap().unwrap(),
+            ApiError::Conflict("taken".to_string())
+        );
+        assert!(errors().decode(StatusCode::BAD_GATEWAY, &body).is_none());
+        assert!(errors()
+            .decode(StatusCode::NOT_FOUND, &Bytes::from_static(b"{"))
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn unmatched_errors_fail_to_encode() {
+        let request = Request::builder().body(()).unwrap().into_parts().0;
+        let errors = one_of_errors().variant::<Json<ApiError>>(StatusCode::NOT_FOUND, |error| {
+            matches!(error, ApiError::NotFound(_))
+        });
+        let failure = errors
+            .encode(ApiError::Conflict("taken".to_string()), &request)
+            .unwrap_err();
+        assert_eq!(failure.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
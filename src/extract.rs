@@ -0,0 +1,837 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::ops::Add;
+
+use async_trait::async_trait;
+use axum::http::header::{HeaderValue, CONTENT_TYPE};
+use axum::http::request::Parts;
+use frunk::hlist::{HCons, HNil};
+use frunk::Generic;
+use hyper::body::Bytes;
+
+use crate::codec::BodyCodec;
+use crate::decode::{DecodeFailure, DecodeFailures, DecodeInput, DecodeReason};
+use crate::encode::EncodeFailure;
+use crate::map::{Mapped, MappedInputs, TryMapped, TryMappedInputs};
+use crate::negotiate::{content_type, essence};
+use crate::openapi::{Components, Operation};
+use crate::tuple::HListTuple;
+
+/// A single input of an endpoint, which can both decode its value from a
+/// request and encode a value into one.
+#[async_trait]
+pub trait Extractor: Sync {
+    type Output: Send;
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<Self::Output, DecodeFailure>;
+
+    /// Like [`Extractor::extract`], for an extractor declared after the given
+    /// number of path segments. Path extractors use the offset to find their
+    /// segment; all other extractors ignore it.
+    async fn extract_at(
+        &self,
+        _path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailure> {
+        self.extract(request, body).await
+    }
+
+    /// The path template segments this extractor matches, e.g. `hello` or
+    /// `{id}`.
+    fn path_segments(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The part of the request this extractor reads, used to name it in
+    /// decode failures.
+    fn input(&self, path_offset: usize) -> DecodeInput;
+
+    /// Writes `value` into a request being built, the inverse of
+    /// [`Extractor::extract_at`].
+    fn encode(
+        &self,
+        value: Self::Output,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure>;
+
+    /// Documents this input, e.g. as a parameter or the request body.
+    fn describe(&self, _operation: &mut Operation, _components: &mut Components) {}
+
+    /// Prepares what this input needs for every request, such as regular
+    /// expressions, when its endpoint is built. Panics if the input is
+    /// declared wrongly.
+    fn compile(&self) {}
+
+    /// Converts the decoded value into a domain type, and back when
+    /// encoding, e.g. `path_capture::<u64>("id").map(ContactId, |ContactId(id)| id)`.
+    fn map<U>(
+        self,
+        decode: fn(Self::Output) -> U,
+        encode: fn(U) -> Self::Output,
+    ) -> Mapped<Self, Self::Output, U>
+    where
+        Self: Sized,
+    {
+        Mapped::new(self, decode, encode)
+    }
+
+    /// Like [`Extractor::map`], rejecting the request with a decode failure
+    /// of this input when the conversion fails.
+    fn try_map<U, Er: Display>(
+        self,
+        decode: fn(Self::Output) -> Result<U, Er>,
+        encode: fn(U) -> Self::Output,
+    ) -> TryMapped<Self, Self::Output, U, Er>
+    where
+        Self: Sized,
+    {
+        TryMapped::new(self, decode, encode)
+    }
+
+    /// Converts the decoded value into a single-field struct deriving frunk's
+    /// `Generic`, e.g. `struct ContactId(u64)`.
+    fn map_to<S>(self) -> Mapped<Self, Self::Output, S>
+    where
+        Self: Sized,
+        S: Generic<Repr = HCons<Self::Output, HNil>>,
+    {
+        self.map(
+            |value| {
+                frunk::from_generic(HCons {
+                    head: value,
+                    tail: HNil,
+                })
+            },
+            |value| frunk::into_generic(value).head,
+        )
+    }
+}
+
+/// Extractors can be shared by reference, e.g. when declared as `static`s.
+#[async_trait]
+impl<E: Extractor + ?Sized> Extractor for &E {
+    type Output = E::Output;
+
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<E::Output, DecodeFailure> {
+        Extractor::extract(*self, request, body).await
+    }
+
+    async fn extract_at(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<E::Output, DecodeFailure> {
+        Extractor::extract_at(*self, path_offset, request, body).await
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        Extractor::path_segments(*self)
+    }
+
+    fn input(&self, path_offset: usize) -> DecodeInput {
+        (**self).input(path_offset)
+    }
+
+    fn encode(
+        &self,
+        value: E::Output,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        Extractor::encode(*self, value, path_offset, request, body)
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        Extractor::describe(*self, operation, components)
+    }
+
+    fn compile(&self) {
+        Extractor::compile(*self)
+    }
+}
+
+/// An input read from and written to a request by a pair of functions.
+pub struct PathExtractor<T> {
+    pub decode: fn(&Parts) -> Result<T, DecodeFailure>,
+    pub encode: fn(T, &mut Parts) -> Result<(), EncodeFailure>,
+}
+
+impl<T> Clone for PathExtractor<T> {
+    fn clone(&self) -> Self {
+        PathExtractor {
+            decode: self.decode,
+            encode: self.encode,
+        }
+    }
+}
+
+/// The request body, decoded with the codec `C`. Requests whose
+/// `Content-Type` differs from the codec's media type are rejected; requests
+/// without a `Content-Type` are decoded as is.
+pub struct BodyExtractor<C>(PhantomData<fn() -> C>);
+
+impl<C> Clone for BodyExtractor<C> {
+    fn clone(&self) -> Self {
+        BodyExtractor(PhantomData)
+    }
+}
+
+/// Declares a body input, e.g. `body::<Json<Contact>>()`.
+pub const fn body<C: BodyCodec>() -> BodyExtractor<C> {
+    BodyExtractor(PhantomData)
+}
+
+#[async_trait]
+impl<T: Send> Extractor for PathExtractor<T> {
+    type Output = T;
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<T, DecodeFailure> {
+        (self.decode)(request)
+    }
+
+    fn input(&self, path_offset: usize) -> DecodeInput {
+        DecodeInput::PathSegment(path_offset)
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        (self.encode)(value, request)
+    }
+}
+
+#[async_trait]
+impl<C> Extractor for BodyExtractor<C>
+where
+    C: BodyCodec,
+    C::Value: Send,
+{
+    type Output = C::Value;
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<C::Value, DecodeFailure> {
+        if let Some(content_type) = content_type(request)? {
+            if content_type != essence(C::MEDIA_TYPE) {
+                return Err(DecodeFailure::new(
+                    DecodeInput::Body,
+                    DecodeReason::UnsupportedMediaType(content_type),
+                ));
+            }
+        }
+        let value = C::decode(body)
+            .map_err(|error| DecodeFailure::deserialize(DecodeInput::Body, error))?;
+        C::validate(&value)
+            .map_err(|rule| DecodeFailure::new(DecodeInput::Body, DecodeReason::Invalid(rule)))?;
+        Ok(value)
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Body
+    }
+
+    fn encode(
+        &self,
+        value: C::Value,
+        _path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        C::validate(&value).map_err(|rule| EncodeFailure::new("body", rule))?;
+        *body = C::encode(&value).map_err(|error| EncodeFailure::new("body", error))?;
+        request
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static(C::MEDIA_TYPE));
+        Ok(())
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.request_content(C::MEDIA_TYPE, C::schema(components));
+    }
+
+    fn compile(&self) {
+        C::compile()
+    }
+}
+
+pub fn empty_endpoint() -> HNil {
+    HNil
+}
+
+/// This trait is used to extract data from a request, and to write the same
+/// data back into a request being built.
+#[async_trait]
+pub trait Extractable: Sync {
+    type Output: Send;
+    async fn extract(&self, parts: &Parts, body: &Bytes) -> Result<Self::Output, DecodeFailure> {
+        self.extract_from(0, parts, body).await
+    }
+
+    /// Like [`Extractable::extract`], for inputs declared after the given
+    /// number of path segments.
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        parts: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailure>;
+
+    /// Like [`Extractable::extract`], but runs every extractor and collects
+    /// all failures instead of stopping at the first one.
+    async fn extract_all(
+        &self,
+        parts: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailures> {
+        self.extract_all_from(0, parts, body).await
+    }
+
+    /// Like [`Extractable::extract_all`], for inputs declared after the given
+    /// number of path segments.
+    async fn extract_all_from(
+        &self,
+        path_offset: usize,
+        parts: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailures> {
+        self.extract_from(path_offset, parts, body)
+            .await
+            .map_err(DecodeFailures::from)
+    }
+
+    /// The path template segments of all extractors, in declaration order.
+    fn path_segments(&self) -> Vec<String>;
+
+    /// Writes the values of all extractors into a request being built.
+    fn encode(
+        &self,
+        value: Self::Output,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        self.encode_from(value, 0, request, body)
+    }
+
+    /// Like [`Extractable::encode`], for inputs declared after the given
+    /// number of path segments.
+    fn encode_from(
+        &self,
+        value: Self::Output,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure>;
+
+    /// Documents all extractors, in declaration order.
+    fn describe(&self, operation: &mut Operation, components: &mut Components);
+
+    /// Prepares all extractors, see [`Extractor::compile`].
+    fn compile(&self);
+
+    /// Renders the path template, e.g. `/hello/{id}`.
+    fn path_template(&self) -> String {
+        format!("/{}", self.path_segments().join("/"))
+    }
+
+    /// Adds an input, an [`Extractor`] or a path literal, after the ones
+    /// already declared, owning it, so the output HList is in declaration
+    /// order. Inputs stored in a `static` can be added by reference, e.g.
+    /// `.with_extractor(&ID)`.
+    fn with_extractor<E>(self, extractor: E) -> <Self as Add<HCons<E, HNil>>>::Output
+    where
+        Self: Sized + Add<HCons<E, HNil>>,
+    {
+        self + HCons {
+            head: extractor,
+            tail: HNil,
+        }
+    }
+
+    /// Converts the output HList into a plain tuple, so server logic can be
+    /// written as `|(name, id, contact)| ...`.
+    fn tupled(self) -> Tupled<Self>
+    where
+        Self: Sized,
+    {
+        Tupled(self)
+    }
+
+    /// Converts the output HList into a struct deriving frunk's `Generic`,
+    /// whose fields are in the order the inputs were declared. This is the
+    /// `map_to` of whole input lists.
+    fn structured<S>(self) -> Structured<Self, S>
+    where
+        Self: Sized,
+        S: Generic<Repr = Self::Output>,
+    {
+        Structured {
+            inputs: self,
+            _output: PhantomData,
+        }
+    }
+
+    /// Converts the output HList into a domain type, and back when encoding.
+    /// Single inputs are better mapped via [`Extractor::map`], which keeps
+    /// them usable as one input among others.
+    fn map_inputs<U>(
+        self,
+        decode: fn(Self::Output) -> U,
+        encode: fn(U) -> Self::Output,
+    ) -> MappedInputs<Self, Self::Output, U>
+    where
+        Self: Sized,
+    {
+        MappedInputs::new(self, decode, encode)
+    }
+
+    /// Like [`Extractable::map_inputs`], rejecting the request with a decode
+    /// failure when the conversion fails.
+    fn try_map_inputs<U, Er: Display>(
+        self,
+        decode: fn(Self::Output) -> Result<U, Er>,
+        encode: fn(U) -> Self::Output,
+    ) -> TryMappedInputs<Self, Self::Output, U, Er>
+    where
+        Self: Sized,
+    {
+        TryMappedInputs::new(self, decode, encode)
+    }
+}
+
+#[async_trait]
+impl<E: Extractor> Extractable for E {
+    type Output = E::Output;
+
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailure> {
+        Extractor::extract_at(self, path_offset, request, body).await
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        Extractor::path_segments(self)
+    }
+
+    fn encode_from(
+        &self,
+        value: Self::Output,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        Extractor::encode(self, value, path_offset, request, body)
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        Extractor::describe(self, operation, components)
+    }
+
+    fn compile(&self) {
+        Extractor::compile(self)
+    }
+}
+
+#[async_trait]
+impl Extractable for HNil {
+    /// The output of extracting from an empty HList is an empty HList.
+    type Output = HNil;
+
+    async fn extract_from(&self, _: usize, _: &Parts, _: &Bytes) -> Result<HNil, DecodeFailure> {
+        Ok(HNil)
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn encode_from(
+        &self,
+        _: HNil,
+        _: usize,
+        _: &mut Parts,
+        _: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        Ok(())
+    }
+
+    fn describe(&self, _: &mut Operation, _: &mut Components) {}
+
+    fn compile(&self) {}
+}
+
+#[async_trait]
+impl<E: Extractor, R: Extractable> Extractable for HCons<E, R> {
+    /// The output of extracting from an HList with a head and a tail is the
+    /// output of extracting from the head and the output of extracting from
+    /// the tail. Extraction stops at the first input that fails to decode.
+    type Output = HCons<E::Output, R::Output>;
+
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailure> {
+        let head = Extractor::extract_at(&self.head, path_offset, request, body).await?;
+        let tail_offset = path_offset + Extractor::path_segments(&self.head).len();
+        let tail = self.tail.extract_from(tail_offset, request, body).await?;
+        Ok(HCons { head, tail })
+    }
+
+    async fn extract_all_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailures> {
+        let head = Extractor::extract_at(&self.head, path_offset, request, body).await;
+        // The tail holds the inputs declared after the head, so its path
+        // segments start after the head's.
+        let tail_offset = path_offset + Extractor::path_segments(&self.head).len();
+        let tail = self.tail.extract_all_from(tail_offset, request, body).await;
+        match (head, tail) {
+            (Ok(head), Ok(tail)) => Ok(HCons { head, tail }),
+            (Err(failure), Ok(_)) => Err(failure.into()),
+            (Ok(_), Err(failures)) => Err(failures),
+            (Err(failure), Err(mut failures)) => {
+                failures.0.insert(0, failure);
+                Err(failures)
+            }
+        }
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        let mut segments = Extractor::path_segments(&self.head);
+        segments.extend(self.tail.path_segments());
+        segments
+    }
+
+    fn encode_from(
+        &self,
+        value: Self::Output,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        Extractor::encode(&self.head, value.head, path_offset, request, body)?;
+        let tail_offset = path_offset + Extractor::path_segments(&self.head).len();
+        self.tail
+            .encode_from(value.tail, tail_offset, request, body)
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        Extractor::describe(&self.head, operation, components);
+        self.tail.describe(operation, components);
+    }
+
+    fn compile(&self) {
+        Extractor::compile(&self.head);
+        self.tail.compile();
+    }
+}
+
+/// Inputs whose output HList is converted into a tuple, see
+/// [`Extractable::tupled`].
+#[derive(Clone)]
+pub struct Tupled<In>(In);
+
+/// Inputs whose output HList is converted into the struct `S`, see
+/// [`Extractable::structured`].
+pub struct Structured<In, S> {
+    inputs: In,
+    _output: PhantomData<fn() -> S>,
+}
+
+impl<In: Clone, S> Clone for Structured<In, S> {
+    fn clone(&self) -> Self {
+        Structured {
+            inputs: self.inputs.clone(),
+            _output: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<In> Extractable for Tupled<In>
+where
+    In: Extractable,
+    In::Output: HListTuple,
+    <In::Output as HListTuple>::Tuple: Send,
+{
+    type Output = <In::Output as HListTuple>::Tuple;
+
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailure> {
+        let value = self.0.extract_from(path_offset, request, body).await?;
+        Ok(value.into_tuple())
+    }
+
+    async fn extract_all_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<Self::Output, DecodeFailures> {
+        let value = self.0.extract_all_from(path_offset, request, body).await?;
+        Ok(value.into_tuple())
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        self.0.path_segments()
+    }
+
+    fn encode_from(
+        &self,
+        value: Self::Output,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        self.0
+            .encode_from(HListTuple::from_tuple(value), path_offset, request, body)
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        self.0.describe(operation, components)
+    }
+
+    fn compile(&self) {
+        self.0.compile()
+    }
+}
+
+#[async_trait]
+impl<In, S> Extractable for Structured<In, S>
+where
+    In: Extractable,
+    S: Generic<Repr = In::Output> + Send,
+{
+    type Output = S;
+
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<S, DecodeFailure> {
+        let value = self.inputs.extract_from(path_offset, request, body).await?;
+        Ok(frunk::from_generic(value))
+    }
+
+    async fn extract_all_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<S, DecodeFailures> {
+        let value = self
+            .inputs
+            .extract_all_from(path_offset, request, body)
+            .await?;
+        Ok(frunk::from_generic(value))
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        self.inputs.path_segments()
+    }
+
+    fn encode_from(
+        &self,
+        value: S,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        self.inputs
+            .encode_from(frunk::into_generic(value), path_offset, request, body)
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        self.inputs.describe(operation, components)
+    }
+
+    fn compile(&self) {
+        self.inputs.compile()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::codec::Json;
+    use crate::decode::DecodeReason;
+    use crate::path::{path_capture, path_literal};
+    use crate::schema::Schema;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Schema)]
+    struct Contact {
+        #[validate(min_length = 1)]
+        name: String,
+    }
+
+    fn parts() -> Parts {
+        Request::builder()
+            .uri("/contacts/x")
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[tokio::test]
+    async fn invalid_bodies_fail_instead_of_panicking() {
+        let body = body::<Json<Contact>>();
+        let failure = Extractor::extract(&body, &parts(), &Bytes::from_static(b"{"))
+            .await
+            .unwrap_err();
+        assert_eq!(failure.input, DecodeInput::Body);
+        assert!(matches!(failure.reason, DecodeReason::Deserialize(_)));
+    }
+
+    #[tokio::test]
+    async fn bodies_check_the_rules_of_their_fields() {
+        let body = body::<Json<Contact>>();
+        let failure = Extractor::extract(&body, &parts(), &Bytes::from_static(br#"{"name":""}"#))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            failure.reason,
+            DecodeReason::Invalid("name: has 0 characters, fewer than 1".to_string())
+        );
+
+        let contact = Contact {
+            name: String::new(),
+        };
+        let (mut request, mut bytes) = (parts(), Bytes::new());
+        let failure = Extractor::encode(&body, contact, 0, &mut request, &mut bytes).unwrap_err();
+        assert_eq!(failure.error, "name: has 0 characters, fewer than 1");
+    }
+
+    #[tokio::test]
+    async fn extraction_stops_at_the_first_failure() {
+        let id = path_capture::<u64>("id");
+        let body = body::<Json<Contact>>();
+        // Inputs are read in declaration order, so the body is read first.
+        let inputs = empty_endpoint().with_extractor(&body).with_extractor(&id);
+        let failure = inputs
+            .extract(&parts(), &Bytes::from_static(b"{"))
+            .await
+            .unwrap_err();
+        assert_eq!(failure.input, DecodeInput::Body);
+    }
+
+    #[tokio::test]
+    async fn extract_all_reports_failures_in_declaration_order() {
+        let contacts = path_literal("contacts");
+        let id = path_capture::<u64>("id");
+        let body = body::<Json<Contact>>();
+        let inputs = empty_endpoint()
+            .with_extractor(&contacts)
+            .with_extractor(&id)
+            .with_extractor(&body);
+        let failures = inputs
+            .extract_all(&parts(), &Bytes::from_static(b"{"))
+            .await
+            .unwrap_err();
+        let inputs: Vec<_> = failures.into_iter().map(|failure| failure.input).collect();
+        assert_eq!(inputs, [DecodeInput::PathSegment(1), DecodeInput::Body]);
+    }
+
+    #[tokio::test]
+    async fn bodies_must_match_the_codec_media_type() {
+        let body = body::<Json<Contact>>();
+        let request = Request::builder()
+            .header("content-type", "text/plain")
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0;
+        let failure = Extractor::extract(&body, &request, &Bytes::from_static(b"{}"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            failure.reason,
+            DecodeReason::UnsupportedMediaType("text/plain".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn encoded_inputs_extract_to_the_same_values() {
+        let contacts = path_literal("contacts");
+        let id = path_capture::<u64>("id");
+        let body = body::<Json<Contact>>();
+        let inputs = empty_endpoint()
+            .with_extractor(&contacts)
+            .with_extractor(&id)
+            .with_extractor(&body);
+        let values = frunk::hlist![
+            7,
+            Contact {
+                name: "Jane".to_string()
+            }
+        ];
+
+        let mut request = Request::builder().body(()).unwrap().into_parts().0;
+        let mut bytes = Bytes::new();
+        inputs.encode(values, &mut request, &mut bytes).unwrap();
+        assert_eq!(request.uri, "/contacts/7");
+        assert_eq!(request.headers["content-type"], "application/json");
+
+        let decoded = inputs.extract(&request, &bytes).await.unwrap();
+        assert_eq!(decoded.head, 7);
+        assert_eq!(decoded.tail.head.name, "Jane");
+    }
+
+    #[derive(Debug, PartialEq, Generic)]
+    struct ContactInput {
+        id: u64,
+        contact: Contact,
+    }
+
+    #[tokio::test]
+    async fn inputs_convert_to_tuples_and_structs() {
+        let contacts = path_literal("contacts");
+        let id = path_capture::<u64>("id");
+        let body = body::<Json<Contact>>();
+        let inputs = empty_endpoint()
+            .with_extractor(&contacts)
+            .with_extractor(&id)
+            .with_extractor(&body);
+        let jane = || Contact {
+            name: "Jane".to_string(),
+        };
+
+        let mut request = Request::builder().body(()).unwrap().into_parts().0;
+        let mut bytes = Bytes::new();
+        let tupled = inputs.tupled();
+        tupled
+            .encode((7, jane()), &mut request, &mut bytes)
+            .unwrap();
+        assert_eq!(request.uri, "/contacts/7");
+        assert_eq!(tupled.path_template(), "/contacts/{id}");
+        let decoded = tupled.extract(&request, &bytes).await.unwrap();
+        assert_eq!(decoded, (7, jane()));
+
+        let structured = inputs.structured::<ContactInput>();
+        let decoded = structured.extract_all(&request, &bytes).await.unwrap();
+        let expected = ContactInput {
+            id: 7,
+            contact: jane(),
+        };
+        assert_eq!(decoded, expected);
+    }
+}
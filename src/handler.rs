@@ -0,0 +1,79 @@
+use std::future::Future;
+
+use frunk::{hlist_pat, HList};
+
+/// Server logic written as a plain async function taking one argument per
+/// input, e.g. `async fn update_contact(id: u64, contact: Contact)` for an
+/// endpoint whose inputs decode to `HList![u64, Contact]`. Path literals
+/// have no value, so `PUT /contacts/{id}` takes the same two arguments.
+/// Implemented for functions of up to 16 arguments.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot handle inputs of type `{Args}` returning `{Res}`",
+    label = "the arguments or result of this function do not match the endpoint",
+    note = "a handler takes one argument per input, in the order the inputs were declared \
+            and without path literals, and returns `Result<_, _>` of the endpoint's outputs \
+            and errors"
+)]
+pub trait Handler<Args, Res> {
+    type Future: Future<Output = Res>;
+
+    fn call(&self, args: Args) -> Self::Future;
+}
+
+macro_rules! handler {
+    ($($arg:ident),*) => {
+        impl<F, Fut, Res, $($arg),*> Handler<HList![$($arg),*], Res> for F
+        where
+            F: Fn($($arg),*) -> Fut,
+            Fut: Future<Output = Res>,
+        {
+            type Future = Fut;
+
+            #[allow(non_snake_case)]
+            fn call(&self, args: HList![$($arg),*]) -> Fut {
+                let hlist_pat![$($arg),*] = args;
+                (self)($($arg),*)
+            }
+        }
+    };
+}
+
+handler!();
+handler!(A);
+handler!(A, B);
+handler!(A, B, C);
+handler!(A, B, C, D);
+handler!(A, B, C, D, E);
+handler!(A, B, C, D, E, G);
+handler!(A, B, C, D, E, G, H);
+handler!(A, B, C, D, E, G, H, I);
+handler!(A, B, C, D, E, G, H, I, J);
+handler!(A, B, C, D, E, G, H, I, J, K);
+handler!(A, B, C, D, E, G, H, I, J, K, L);
+handler!(A, B, C, D, E, G, H, I, J, K, L, M);
+handler!(A, B, C, D, E, G, H, I, J, K, L, M, N);
+handler!(A, B, C, D, E, G, H, I, J, K, L, M, N, O);
+handler!(A, B, C, D, E, G, H, I, J, K, L, M, N, O, P);
+handler!(A, B, C, D, E, G, H, I, J, K, L, M, N, O, P, Q);
+
+#[cfg(test)]
+mod tests {
+    use frunk::hlist;
+
+    use super::*;
+
+    async fn greet(greeting: &'static str, name: String, times: usize) -> String {
+        format!("{}, {}", greeting, name).repeat(times)
+    }
+
+    async fn ping() -> &'static str {
+        "pong"
+    }
+
+    #[tokio::test]
+    async fn handlers_take_one_argument_per_input_in_order() {
+        let greeting = Handler::call(&greet, hlist!["Hello", "Jane".to_string(), 2]).await;
+        assert_eq!(greeting, "Hello, JaneHello, Jane");
+        assert_eq!(Handler::call(&ping, hlist![]).await, "pong");
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use axum::http::header::{HeaderName, HeaderValue};
+use axum::http::request::Parts;
+use hyper::body::Bytes;
+
+use crate::decode::{DecodeFailure, DecodeInput};
+use crate::encode::EncodeFailure;
+use crate::extract::Extractor;
+use crate::openapi::{Components, Operation, Parameter, ParameterLocation};
+use crate::schema::Schema;
+
+/// A required header, parsed via [`FromStr`].
+#[derive(Clone)]
+pub struct HeaderExtractor<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+/// An optional header, extracted as `None` when absent.
+#[derive(Clone)]
+pub struct OptionalHeaderExtractor<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+/// A header that may occur several times or hold a comma-separated list,
+/// e.g. `If-None-Match: "a", "b"`.
+#[derive(Clone)]
+pub struct RepeatedHeaderExtractor<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+pub const fn header<T>(name: &'static str) -> HeaderExtractor<T> {
+    HeaderExtractor {
+        name,
+        _output: PhantomData,
+    }
+}
+
+pub const fn optional_header<T>(name: &'static str) -> OptionalHeaderExtractor<T> {
+    OptionalHeaderExtractor {
+        name,
+        _output: PhantomData,
+    }
+}
+
+pub const fn repeated_header<T>(name: &'static str) -> RepeatedHeaderExtractor<T> {
+    RepeatedHeaderExtractor {
+        name,
+        _output: PhantomData,
+    }
+}
+
+/// Returns the values of every occurrence of the header `name`, in order.
+pub fn header_values<'a>(request: &'a Parts, name: &str) -> Result<Vec<&'a str>, DecodeFailure> {
+    request
+        .headers
+        .get_all(name)
+        .iter()
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|error| DecodeFailure::parse(DecodeInput::Header(name.to_string()), error))
+        })
+        .collect()
+}
+
+/// Appends the header `name` to a request being built.
+pub fn append_header(request: &mut Parts, name: &str, value: &str) -> Result<(), EncodeFailure> {
+    let target = || format!("header `{}`", name);
+    let header_name = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|error| EncodeFailure::new(target(), error))?;
+    let header_value =
+        HeaderValue::from_str(value).map_err(|error| EncodeFailure::new(target(), error))?;
+    request.headers.append(header_name, header_value);
+    Ok(())
+}
+
+fn parse_header_value<T>(name: &str, value: &str) -> Result<T, DecodeFailure>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|error| DecodeFailure::parse(DecodeInput::Header(name.to_string()), error))
+}
+
+#[async_trait]
+impl<T> Extractor for HeaderExtractor<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = T;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<T, DecodeFailure> {
+        match header_values(request, self.name)?.first() {
+            Some(value) => parse_header_value(self.name, value),
+            None => Err(DecodeFailure::missing(DecodeInput::Header(
+                self.name.to_string(),
+            ))),
+        }
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Header(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        append_header(request, self.name, &value.to_string())
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Header,
+            true,
+            T::schema(components),
+        ));
+    }
+}
+
+#[async_trait]
+impl<T> Extractor for OptionalHeaderExtractor<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = Option<T>;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<Option<T>, DecodeFailure> {
+        header_values(request, self.name)?
+            .first()
+            .map(|value| parse_header_value(self.name, value))
+            .transpose()
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Header(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: Option<T>,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        match value {
+            Some(value) => append_header(request, self.name, &value.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Header,
+            false,
+            T::schema(components),
+        ));
+    }
+}
+
+#[async_trait]
+impl<T> Extractor for RepeatedHeaderExtractor<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = Vec<T>;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<Vec<T>, DecodeFailure> {
+        header_values(request, self.name)?
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| parse_header_value(self.name, value))
+            .collect()
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Header(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: Vec<T>,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        if value.is_empty() {
+            return Ok(());
+        }
+        let values: Vec<String> = value.iter().map(|value| value.to_string()).collect();
+        append_header(request, self.name, &values.join(", "))
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Header,
+            false,
+            Vec::<T>::schema(components),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn headers_are_trimmed_and_split() {
+        let request = Request::builder()
+            .header("x-page", " 3 ")
+            .header("accept-language", "en, da")
+            .header("accept-language", "de")
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0;
+        let body = Bytes::new();
+
+        let page = Extractor::extract(&header::<u32>("x-page"), &request, &body).await;
+        assert_eq!(page.unwrap(), 3);
+        let languages = Extractor::extract(
+            &repeated_header::<String>("accept-language"),
+            &request,
+            &body,
+        )
+        .await;
+        assert_eq!(languages.unwrap(), ["en", "da", "de"]);
+        let missing =
+            Extractor::extract(&optional_header::<String>("x-request-id"), &request, &body).await;
+        assert_eq!(missing.unwrap(), None);
+        let required = Extractor::extract(&header::<String>("x-request-id"), &request, &body).await;
+        assert_eq!(
+            required.unwrap_err(),
+            DecodeFailure::missing(DecodeInput::Header("x-request-id".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn appended_headers_are_read_back() {
+        let mut request = Request::builder().body(()).unwrap().into_parts().0;
+        let mut body = Bytes::new();
+        let languages = repeated_header::<String>("accept-language");
+        let languages_value = vec!["en".to_string(), "da".to_string()];
+        Extractor::encode(&languages, languages_value, 0, &mut request, &mut body).unwrap();
+        assert_eq!(request.headers["accept-language"], "en, da");
+
+        let decoded = Extractor::extract(&languages, &request, &body).await;
+        assert_eq!(decoded.unwrap(), ["en", "da"]);
+        assert!(append_header(&mut request, "x-bad", "line\nbreak").is_err());
+    }
+}
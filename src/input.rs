@@ -0,0 +1,152 @@
+use axum::http::Method;
+use frunk::hlist::HNil;
+
+use crate::endpoint::{endpoint, Endpoint, NoErrors};
+use crate::extract::Extractable;
+use crate::map::MappedInputs;
+
+pub use tapi_derive::EndpointInput;
+
+/// A struct holding the inputs of an endpoint, one per field, along with
+/// the endpoint's method. Implement it with `#[derive(EndpointInput)]`:
+///
+/// ```ignore
+/// #[derive(EndpointInput)]
+/// #[endpoint(PUT "/contacts/{id}")]
+/// struct ReplaceContact {
+///     #[path_capture]
+///     id: u64,
+///     #[header("x-request-id")]
+///     request_id: Option<String>,
+///     #[body(json)]
+///     contact: Contact,
+/// }
+/// ```
+///
+/// `Option` and `Vec` fields read optional and repeated query parameters and
+/// headers.
+pub trait EndpointInput: Sized {
+    /// The extractors of the fields: the path segments of the template in
+    /// order, then the other fields in declaration order.
+    type Inputs: Extractable;
+
+    fn method() -> Method;
+
+    fn inputs() -> Self::Inputs;
+
+    fn from_inputs(inputs: <Self::Inputs as Extractable>::Output) -> Self;
+
+    fn into_inputs(self) -> <Self::Inputs as Extractable>::Output;
+}
+
+/// The inputs of an endpoint started by [`endpoint_input`]: the extractors of
+/// `I`'s fields, converted to and from an `I`.
+pub type EndpointInputs<I> = MappedInputs<
+    <I as EndpointInput>::Inputs,
+    <<I as EndpointInput>::Inputs as Extractable>::Output,
+    I,
+>;
+
+/// Starts an endpoint with the method and inputs declared by `I`, handing
+/// the inputs to the server logic as an `I`.
+pub fn endpoint_input<I>() -> Endpoint<EndpointInputs<I>, NoErrors, HNil>
+where
+    I: EndpointInput + Send,
+{
+    endpoint(I::method(), I::inputs()).map_input(I::from_inputs, I::into_inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::request::Parts;
+    use axum::http::Request;
+    use hyper::body::Bytes;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::openapi::{operation, Components};
+    use crate::schema::Schema;
+
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Schema)]
+    struct Contact {
+        name: String,
+    }
+
+    /// Captures declared out of template order, names defaulting to the
+    /// field's.
+    #[derive(Debug, PartialEq, EndpointInput)]
+    #[endpoint(PATCH "/users/{user}/contacts/{id}")]
+    struct PatchContact {
+        #[path_capture]
+        id: u64,
+        #[query]
+        tags: Vec<String>,
+        #[query("dry-run")]
+        dry_run: Option<bool>,
+        #[header]
+        request_id: Option<String>,
+        #[cookie(name = "sid")]
+        session: String,
+        #[path_capture]
+        user: String,
+        #[body]
+        contact: Contact,
+    }
+
+    fn patch_contact() -> PatchContact {
+        PatchContact {
+            id: 7,
+            tags: vec!["friends".to_string(), "work".to_string()],
+            dry_run: Some(true),
+            request_id: None,
+            session: "s1".to_string(),
+            user: "ada".to_string(),
+            contact: Contact {
+                name: "Jane".to_string(),
+            },
+        }
+    }
+
+    fn request() -> Parts {
+        Request::builder().body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn derived_inputs_follow_the_path_template() {
+        let endpoint = endpoint_input::<PatchContact>();
+        assert_eq!(endpoint.method, Method::PATCH);
+        assert_eq!(endpoint.path_template(), "/users/{user}/contacts/{id}");
+
+        let operation = operation(&endpoint, &mut Components::default());
+        let parameters: Vec<_> = operation
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name.as_str())
+            .collect();
+        assert_eq!(
+            parameters,
+            ["user", "id", "tags", "dry-run", "request-id", "sid"]
+        );
+        assert!(operation.request_body.is_some());
+    }
+
+    #[tokio::test]
+    async fn derived_inputs_encode_and_extract_the_struct() {
+        let endpoint = endpoint_input::<PatchContact>();
+        let mut request = request();
+        let mut body = Bytes::new();
+        endpoint
+            .input
+            .encode(patch_contact(), &mut request, &mut body)
+            .unwrap();
+        assert_eq!(
+            request.uri,
+            "/users/ada/contacts/7?tags=friends&tags=work&dry-run=true"
+        );
+        assert_eq!(request.headers["cookie"], "sid=s1");
+        assert!(!request.headers.contains_key("request-id"));
+
+        let extracted = endpoint.input.extract(&request, &body).await.unwrap();
+        assert_eq!(extracted, patch_contact());
+    }
+}
@@ -0,0 +1,42 @@
+// Lets the derive macros refer to this crate as `::tapi`, both from here and
+// from crates depending on it.
+extern crate self as tapi;
+
+// `#[derive(Generic)]` refers to `::frunk_core`, which `frunk` re-exports.
+#[cfg(test)]
+extern crate frunk as frunk_core;
+
+pub mod client;
+pub mod codec;
+pub mod cookie;
+pub mod decode;
+pub mod docs;
+pub mod encode;
+pub mod endpoint;
+pub mod error_output;
+pub mod extract;
+pub mod handler;
+pub mod header;
+pub mod input;
+pub mod map;
+pub mod negotiate;
+pub mod openapi;
+pub mod output;
+pub mod path;
+pub mod query;
+pub mod schema;
+pub mod security;
+pub mod server;
+pub mod service;
+pub mod tuple;
+pub mod validate;
+
+// Dependencies the derive macros' output refers to, so that crates using the
+// derives need not depend on them directly. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use axum;
+    pub use frunk;
+    pub use regex;
+    pub use serde_json;
+}
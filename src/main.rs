@@ -1,149 +1,385 @@
-use async_trait::async_trait;
-use axum::http::Request;
-use axum::{body::Body, http::request::Parts};
-use frunk::hlist::{HCons, HNil};
-use hyper::body::Bytes;
-use serde::de::DeserializeOwned;
+// `#[derive(Generic)]` refers to `::frunk_core`, which `frunk` re-exports.
+extern crate frunk as frunk_core;
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::http::{Method, StatusCode};
+use axum::Router;
+use frunk::hlist::HNil;
+use frunk::{hlist, Generic, HList};
 use serde::{Deserialize, Serialize};
 
-#[async_trait(?Send)]
-trait Extractor {
-    type Output;
-    async fn extract(&self, request: &Parts, body: &Bytes) -> Self::Output;
+use tapi::client::client;
+use tapi::codec::Json;
+use tapi::cookie::{cookie, CookieExtractor};
+use tapi::endpoint::{endpoint, Endpoint};
+use tapi::error_output::{one_of_errors, OneOfErrors};
+use tapi::extract::{body, empty_endpoint, BodyExtractor, Extractable, Extractor};
+use tapi::header::{optional_header, OptionalHeaderExtractor};
+use tapi::input::{endpoint_input, EndpointInput};
+use tapi::openapi::openapi;
+use tapi::output::{
+    empty_output, response_body, response_header, BodyOutput, Encodable, HeaderOutput,
+};
+use tapi::path::{path_capture, path_literal, PathCapture, PathLiteral};
+use tapi::query::{query_or, repeated_query, DefaultQueryExtractor, RepeatedQueryExtractor};
+use tapi::schema::Schema;
+use tapi::security::{bearer, BearerAuth};
+use tapi::server::RouterExt;
+use tapi::validate::{validated, Validated, Validator};
+
+#[derive(Debug, Deserialize, Serialize, Schema)]
+struct Contact {
+    #[validate(min_length = 1)]
+    name: String,
+    email: String,
+    #[validate(minimum = 18, maximum = 120)]
+    age: u8,
+}
+
+#[derive(Debug, Deserialize, Serialize, Schema)]
+enum ApiError {
+    NotFound { id: u64 },
+    Conflict { reason: String },
+    Unauthorized { reason: String },
 }
 
-struct PathExtractor<T>(fn(&Parts) -> T);
+static HELLO: PathLiteral = path_literal("hello");
 
-struct BodyExtractor<T>(fn(&Bytes) -> T)
-where
-    T: DeserializeOwned;
+static ID: PathCapture<u64> = path_capture("id");
 
-#[async_trait(?Send)]
-impl<T> Extractor for PathExtractor<T> {
-    type Output = T;
-    async fn extract(&self, request: &Parts, _body: &Bytes) -> T {
-        self.0(request)
-    }
+static PAGE: Validated<DefaultQueryExtractor<u32>> = validated(
+    query_or("page", 1),
+    &[Validator::Minimum(1.0), Validator::Maximum(1000.0)],
+);
+
+static TAGS: Validated<RepeatedQueryExtractor<String>> = validated(
+    repeated_query("tags"),
+    &[Validator::MaxItems(10), Validator::Pattern("^[a-z-]+$")],
+);
+
+static REQUEST_ID: OptionalHeaderExtractor<String> = optional_header("x-request-id");
+
+static SESSION: CookieExtractor<String> = cookie("session");
+
+static CONTACT: BodyExtractor<Json<Contact>> = body();
+
+static ECHO_REQUEST_ID: HeaderOutput<String> = response_header("x-request-id");
+
+static CONTACT_BODY: BodyOutput<Json<Contact>> = response_body();
+
+async fn health() -> Result<HNil, Infallible> {
+    Ok(HNil)
 }
 
-#[async_trait(?Send)]
-impl<T> Extractor for BodyExtractor<T>
-where
-    T: DeserializeOwned,
-{
-    type Output = T;
-    async fn extract(&self, _request: &Parts, body: &Bytes) -> T {
-        let result: T = serde_json::from_slice(body).unwrap();
-        result
-    }
+/// The outputs of `PUT /hello/{id}`, in declaration order.
+#[derive(Debug, Generic)]
+struct UpdatedContact {
+    request_id: String,
+    contact: Contact,
 }
 
-fn empty_endpoint() -> HNil {
-    HNil
+/// The inputs of `PUT /hello/{id}`, in declaration order.
+type UpdateContactInput = (u64, u32, Vec<String>, Option<String>, String, Contact);
+
+async fn update_contact(
+    (id, _page, _tags, request_id, _session, contact): UpdateContactInput,
+) -> Result<UpdatedContact, ApiError> {
+    match id {
+        0 => Err(ApiError::NotFound { id }),
+        _ if contact.age < 18 => Err(ApiError::Conflict {
+            reason: "contacts must be adults".to_string(),
+        }),
+        _ => Ok(UpdatedContact {
+            request_id: request_id.unwrap_or_default(),
+            contact,
+        }),
+    }
 }
 
-/// This trait is used to extract data from a request.
-#[async_trait(?Send)]
-trait Extractable {
-    type Output;
-    async fn extract(&self, parts: &Parts, body: &Bytes) -> Self::Output;
+fn update_contact_endpoint() -> Endpoint<
+    impl Extractable<Output = UpdateContactInput> + Send + 'static,
+    OneOfErrors<ApiError>,
+    impl Encodable<Input = UpdatedContact> + Send + Sync + 'static,
+> {
+    let inputs = empty_endpoint()
+        .with_extractor(&HELLO)
+        .with_extractor(&ID)
+        .with_extractor(&PAGE)
+        .with_extractor(&TAGS)
+        .with_extractor(&REQUEST_ID)
+        .with_extractor(&SESSION)
+        .with_extractor(&CONTACT);
+
+    let outputs = empty_output()
+        .with_encoder(&ECHO_REQUEST_ID)
+        .with_encoder(&CONTACT_BODY);
+
+    let errors = one_of_errors::<ApiError>()
+        .variant::<Json<ApiError>>(StatusCode::NOT_FOUND, |error| {
+            matches!(error, ApiError::NotFound { .. })
+        })
+        .variant::<Json<ApiError>>(StatusCode::CONFLICT, |error| {
+            matches!(error, ApiError::Conflict { .. })
+        });
+
+    endpoint(Method::PUT, inputs)
+        .tupled()
+        .output(outputs)
+        .map_output_to::<UpdatedContact>()
+        .errors(errors)
+        .name("updateContact")
+}
 
-    fn with_extractor<E: Extractor>(self, extractor: &E) -> HCons<&E, Self>
-    where
-        Self: Sized,
-    {
-        HCons {
-            head: extractor,
-            tail: self,
-        }
+async fn authenticate(token: String) -> Result<String, ApiError> {
+    match token.as_str() {
+        "secret" => Ok("ada".to_string()),
+        _ => Err(ApiError::Unauthorized {
+            reason: "unknown token".to_string(),
+        }),
     }
 }
 
-#[async_trait(?Send)]
-impl<E: Extractor> Extractable for E {
-    type Output = E::Output;
+/// A contact's id, documented as the integer it is sent as.
+#[derive(Generic)]
+struct ContactId(u64);
 
-    async fn extract(&self, request: &Parts, body: &Bytes) -> Self::Output {
-        self.extract(request, body).await
+/// The inputs of `GET /api/v1/contacts/{id}`, besides the bearer token, in
+/// declaration order.
+#[derive(Generic)]
+struct GetContactInput {
+    id: ContactId,
+}
+
+async fn get_contact(
+    user: String,
+    GetContactInput { id: ContactId(id) }: GetContactInput,
+) -> Result<HList![Contact], ApiError> {
+    match id {
+        0 => Err(ApiError::NotFound { id }),
+        _ => Ok(hlist![Contact {
+            email: format!("{}@example.com", user),
+            name: user,
+            age: 36,
+        }]),
     }
 }
 
-#[async_trait(?Send)]
-impl Extractable for HNil {
-    /// The output of extracting from an empty HList is an empty HList.
-    type Output = HNil;
+/// The inputs every endpoint under `/api/v1` starts with, its path prefix.
+type ApiBaseInput = HList![PathLiteral, PathLiteral];
+
+/// What every endpoint under `/api/v1` shares: the path prefix, a bearer
+/// token and the error responses.
+fn api_base() -> Endpoint<ApiBaseInput, OneOfErrors<ApiError>, HNil, BearerAuth> {
+    let errors = one_of_errors::<ApiError>()
+        .variant::<Json<ApiError>>(StatusCode::UNAUTHORIZED, |error| {
+            matches!(error, ApiError::Unauthorized { .. })
+        })
+        .variant::<Json<ApiError>>(StatusCode::NOT_FOUND, |error| {
+            matches!(error, ApiError::NotFound { .. })
+        });
+
+    endpoint(Method::GET, empty_endpoint())
+        .input(path_literal("api"))
+        .input(path_literal("v1"))
+        .security_input(bearer())
+        .errors(errors)
+}
+
+fn get_contact_endpoint(
+    base: Endpoint<ApiBaseInput, OneOfErrors<ApiError>, HNil, BearerAuth>,
+) -> Endpoint<
+    impl Extractable<Output = GetContactInput> + Send + 'static,
+    OneOfErrors<ApiError>,
+    impl Encodable<Input = HList![Contact]> + Send + Sync + 'static,
+    BearerAuth,
+> {
+    base.input(path_literal("contacts"))
+        .input(path_capture::<u64>("id").map_to::<ContactId>())
+        .structured::<GetContactInput>()
+        .output(empty_output().with_encoder(&CONTACT_BODY))
+        .name("getContact")
+}
 
-    async fn extract(&self, _: &Parts, _: &Bytes) -> Self::Output {
-        HNil
+/// A plain service function, served via [`RouterExt::secure_handler`]: the
+/// principal, then one argument per input.
+async fn create_contact(user: String, contact: Contact) -> Result<Contact, ApiError> {
+    match contact.email.split_once('@') {
+        Some((name, _)) if name == user => Ok(contact),
+        _ => Err(ApiError::Conflict {
+            reason: format!("{} can only create their own contact", user),
+        }),
     }
 }
 
-#[async_trait(?Send)]
-impl<E: Extractor, R: Extractable> Extractable for HCons<&E, R> {
-    /// The output of extracting from an HList with a head and a tail is the
-    /// output of extracting from the head and the output of extracting from
-    /// the tail.
-    type Output = HCons<E::Output, R::Output>;
+/// The inputs of `POST /api/v1/contacts`, besides the bearer token.
+type CreateContactInput = HList![Contact];
+
+fn create_contact_endpoint(
+    base: Endpoint<ApiBaseInput, OneOfErrors<ApiError>, HNil, BearerAuth>,
+) -> Endpoint<
+    impl Extractable<Output = CreateContactInput> + Send + 'static,
+    OneOfErrors<ApiError>,
+    BodyOutput<Json<Contact>>,
+    BearerAuth,
+> {
+    base.method(Method::POST)
+        .input(path_literal("contacts"))
+        .input(body::<Json<Contact>>())
+        .output(response_body())
+        .status(StatusCode::CREATED)
+        .name("createContact")
+}
+
+/// The inputs of `PUT /contacts/{id}`, declared by their struct.
+#[derive(EndpointInput)]
+#[endpoint(PUT "/contacts/{id}")]
+struct ReplaceContact {
+    #[path_capture]
+    id: u64,
+    #[query(name = "dry-run")]
+    dry_run: Option<bool>,
+    #[header("x-request-id")]
+    request_id: Option<String>,
+    #[cookie]
+    session: String,
+    #[body(json)]
+    contact: Contact,
+}
 
-    async fn extract(&self, request: &Parts, body: &Bytes) -> Self::Output {
-        let head: <E as Extractor>::Output = self.head.extract(request, body).await;
-        let tail: R::Output = self.tail.extract(request, body).await;
-        HCons { head, tail }
+async fn replace_contact(
+    ReplaceContact {
+        id,
+        dry_run,
+        contact,
+        ..
+    }: ReplaceContact,
+) -> Result<Contact, ApiError> {
+    match (id, dry_run) {
+        (0, _) => Err(ApiError::NotFound { id }),
+        (_, Some(true)) => Ok(contact),
+        _ => Ok(Contact {
+            name: contact.name.to_uppercase(),
+            ..contact
+        }),
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct Contact {
-    name: String,
-    email: String,
-    age: u8,
+fn replace_contact_endpoint() -> Endpoint<
+    impl Extractable<Output = ReplaceContact> + Send + 'static,
+    OneOfErrors<ApiError>,
+    BodyOutput<Json<Contact>>,
+> {
+    let errors = one_of_errors::<ApiError>()
+        .variant::<Json<ApiError>>(StatusCode::NOT_FOUND, |error| {
+            matches!(error, ApiError::NotFound { .. })
+        });
+
+    endpoint_input::<ReplaceContact>()
+        .output(response_body())
+        .errors(errors)
+        .name("replaceContact")
 }
 
-#[tokio::main]
-async fn main() {
-    let endpoint = empty_endpoint();
+fn get_contact_input(id: u64) -> GetContactInput {
+    GetContactInput { id: ContactId(id) }
+}
 
-    let contact = Contact {
-        name: "John Doe".to_string(),
-        email: "foo@john.com".to_string(),
-        age: 42,
-    };
+fn contact(age: u8) -> Contact {
+    Contact {
+        name: "Ada".to_string(),
+        email: "ada@example.com".to_string(),
+        age,
+    }
+}
 
-    let contact_as_json = serde_json::to_string(&contact).unwrap();
+#[tokio::main]
+async fn main() {
+    // Built once, then shared by the server, the client and the docs.
+    let served_health = Arc::new(endpoint(Method::GET, path_literal("health")));
+    let served_update_contact = Arc::new(update_contact_endpoint());
+    let served_get_contact = Arc::new(get_contact_endpoint(api_base()));
+    let served_create_contact = Arc::new(create_contact_endpoint(api_base()));
+    let served_replace_contact = Arc::new(replace_contact_endpoint());
 
-    let request: Request<Body> = Request::builder()
-        .uri("/hello/1337")
-        .body(Body::from(contact_as_json))
-        .unwrap();
+    let docs = openapi("tapi.rs demo", "0.1.0")
+        .endpoint(&served_health)
+        .endpoint(&served_update_contact)
+        .endpoint(&served_get_contact)
+        .endpoint(&served_create_contact)
+        .endpoint(&served_replace_contact);
+    println!("{}", docs.to_yaml());
 
-    let (parts, body) = request.into_parts();
+    println!("serving GET {}", served_health.path_template());
+    println!("serving PUT {}", served_update_contact.path_template());
+    println!("serving GET {}", served_get_contact.path_template());
+    println!("serving POST {}", served_create_contact.path_template());
+    println!("serving PUT {}", served_replace_contact.path_template());
+    println!("serving docs at /docs");
 
-    let extract_first_part: PathExtractor<String> =
-        PathExtractor(|request| request.uri.path().split("/").nth(1).unwrap().to_string());
+    let app = Router::new()
+        .handler(served_health, health)
+        .endpoint(served_update_contact.clone(), update_contact)
+        .secure_endpoint(served_get_contact.clone(), authenticate, get_contact)
+        .secure_handler(served_create_contact.clone(), authenticate, create_contact)
+        .endpoint(served_replace_contact.clone(), replace_contact)
+        .docs(&docs, "/docs");
 
-    let extract_second_part: PathExtractor<u64> = PathExtractor(|request| {
-        request
-            .uri
-            .path()
-            .split("/")
-            .nth(2)
-            .unwrap()
-            .parse::<u64>()
-            .unwrap()
-    });
+    let address = SocketAddr::from(([127, 0, 0, 1], 3000));
+    let server = axum::Server::bind(&address).serve(app.into_make_service());
+    let base_url = format!("http://{}", server.local_addr());
+    let server = tokio::spawn(server);
 
-    let extract_contact_from_body: BodyExtractor<Contact> =
-        BodyExtractor(|body| serde_json::from_slice(body).unwrap());
+    // The same endpoint description, interpreted as a client.
+    let update_contact_client = client(served_update_contact, base_url.clone());
+    let updated = update_contact_client
+        .call((
+            7,
+            2,
+            vec!["friends".to_string()],
+            Some("request-1".to_string()),
+            "session-1".to_string(),
+            contact(36),
+        ))
+        .await;
+    println!("PUT /hello/7: {:?}", updated);
+    let not_found = update_contact_client
+        .call((0, 1, vec![], None, "session-1".to_string(), contact(36)))
+        .await;
+    println!("PUT /hello/0: {:?}", not_found);
 
-    let endpoint2 = endpoint
-        .with_extractor(&extract_first_part)
-        .with_extractor(&extract_second_part)
-        .with_extractor(&extract_contact_from_body);
+    let get_contact_client = client(served_get_contact, base_url.clone());
+    let found = get_contact_client
+        .call_secure("secret".to_string(), get_contact_input(7))
+        .await;
+    println!("GET /api/v1/contacts/7: {:?}", found);
+    let unauthorized = get_contact_client
+        .call_secure("guess".to_string(), get_contact_input(7))
+        .await;
+    println!(
+        "GET /api/v1/contacts/7 with an unknown token: {:?}",
+        unauthorized
+    );
 
-    let bytes: Bytes = hyper::body::to_bytes(body).await.unwrap();
+    let create_contact_client = client(served_create_contact, base_url.clone());
+    let created = create_contact_client
+        .call_secure("secret".to_string(), hlist![contact(36)])
+        .await;
+    println!("POST /api/v1/contacts: {:?}", created);
 
-    let result: HCons<Contact, HCons<u64, HCons<String, HNil>>> =
-        endpoint2.extract(&parts, &bytes).await;
+    let replace_contact_client = client(served_replace_contact, base_url);
+    let replaced = replace_contact_client
+        .call(ReplaceContact {
+            id: 7,
+            dry_run: None,
+            request_id: Some("request-2".to_string()),
+            session: "session-1".to_string(),
+            contact: contact(36),
+        })
+        .await;
+    println!("PUT /contacts/7: {:?}", replaced);
 
-    print!("{:?}", result);
+    server.await.unwrap().unwrap();
 }
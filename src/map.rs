@@ -0,0 +1,516 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::request::Parts;
+use axum::http::{response, Response};
+use hyper::body::Bytes;
+
+use crate::decode::{DecodeFailure, DecodeFailures, DecodeInput};
+use crate::encode::EncodeFailure;
+use crate::extract::{Extractable, Extractor};
+use crate::openapi::{ApiResponse, Components, Operation};
+use crate::output::{Encodable, Encoder};
+
+/// An input or output whose value is converted to and from a domain type,
+/// e.g. `path_capture::<u64>("id").map(ContactId, |ContactId(id)| id)`.
+/// Documentation still shows the wire type, here an integer.
+pub struct Mapped<E, T, U> {
+    inner: E,
+    decode: fn(T) -> U,
+    encode: fn(U) -> T,
+}
+
+/// Like [`Mapped`], for conversions that can fail, e.g. into a type that
+/// only allows some values. A failed conversion is reported as a parse
+/// failure.
+pub struct TryMapped<E, T, U, Er> {
+    inner: E,
+    decode: fn(T) -> Result<U, Er>,
+    encode: fn(U) -> T,
+}
+
+/// Inputs whose output HList is converted to and from a domain type, see
+/// [`Extractable::map_inputs`].
+pub struct MappedInputs<In, T, U> {
+    inputs: In,
+    decode: fn(T) -> U,
+    encode: fn(U) -> T,
+}
+
+/// Like [`MappedInputs`], for conversions that can fail, see
+/// [`Extractable::try_map_inputs`].
+pub struct TryMappedInputs<In, T, U, Er> {
+    inputs: In,
+    decode: fn(T) -> Result<U, Er>,
+    encode: fn(U) -> T,
+}
+
+impl<E, T, U> Mapped<E, T, U> {
+    pub(crate) fn new(inner: E, decode: fn(T) -> U, encode: fn(U) -> T) -> Self {
+        Mapped {
+            inner,
+            decode,
+            encode,
+        }
+    }
+}
+
+impl<E, T, U, Er> TryMapped<E, T, U, Er> {
+    pub(crate) fn new(inner: E, decode: fn(T) -> Result<U, Er>, encode: fn(U) -> T) -> Self {
+        TryMapped {
+            inner,
+            decode,
+            encode,
+        }
+    }
+}
+
+impl<In, T, U> MappedInputs<In, T, U> {
+    pub(crate) fn new(inputs: In, decode: fn(T) -> U, encode: fn(U) -> T) -> Self {
+        MappedInputs {
+            inputs,
+            decode,
+            encode,
+        }
+    }
+}
+
+impl<In, T, U, Er> TryMappedInputs<In, T, U, Er> {
+    pub(crate) fn new(inputs: In, decode: fn(T) -> Result<U, Er>, encode: fn(U) -> T) -> Self {
+        TryMappedInputs {
+            inputs,
+            decode,
+            encode,
+        }
+    }
+}
+
+impl<E: Clone, T, U> Clone for Mapped<E, T, U> {
+    fn clone(&self) -> Self {
+        Mapped::new(self.inner.clone(), self.decode, self.encode)
+    }
+}
+
+impl<E: Clone, T, U, Er> Clone for TryMapped<E, T, U, Er> {
+    fn clone(&self) -> Self {
+        TryMapped::new(self.inner.clone(), self.decode, self.encode)
+    }
+}
+
+impl<In: Clone, T, U> Clone for MappedInputs<In, T, U> {
+    fn clone(&self) -> Self {
+        MappedInputs::new(self.inputs.clone(), self.decode, self.encode)
+    }
+}
+
+impl<In: Clone, T, U, Er> Clone for TryMappedInputs<In, T, U, Er> {
+    fn clone(&self) -> Self {
+        TryMappedInputs::new(self.inputs.clone(), self.decode, self.encode)
+    }
+}
+
+#[async_trait]
+impl<E, T, U> Extractor for Mapped<E, T, U>
+where
+    E: Extractor<Output = T>,
+    T: Send,
+    U: Send,
+{
+    type Output = U;
+
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<U, DecodeFailure> {
+        self.extract_at(0, request, body).await
+    }
+
+    async fn extract_at(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<U, DecodeFailure> {
+        let value = Extractor::extract_at(&self.inner, path_offset, request, body).await?;
+        Ok((self.decode)(value))
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        Extractor::path_segments(&self.inner)
+    }
+
+    fn input(&self, path_offset: usize) -> DecodeInput {
+        self.inner.input(path_offset)
+    }
+
+    fn encode(
+        &self,
+        value: U,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        Extractor::encode(
+            &self.inner,
+            (self.encode)(value),
+            path_offset,
+            request,
+            body,
+        )
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        Extractor::describe(&self.inner, operation, components)
+    }
+
+    fn compile(&self) {
+        Extractor::compile(&self.inner)
+    }
+}
+
+#[async_trait]
+impl<E, T, U, Er> Extractor for TryMapped<E, T, U, Er>
+where
+    E: Extractor<Output = T>,
+    T: Send,
+    U: Send,
+    Er: Display,
+{
+    type Output = U;
+
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<U, DecodeFailure> {
+        self.extract_at(0, request, body).await
+    }
+
+    async fn extract_at(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<U, DecodeFailure> {
+        let value = Extractor::extract_at(&self.inner, path_offset, request, body).await?;
+        (self.decode)(value)
+            .map_err(|error| DecodeFailure::parse(self.inner.input(path_offset), error))
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        Extractor::path_segments(&self.inner)
+    }
+
+    fn input(&self, path_offset: usize) -> DecodeInput {
+        self.inner.input(path_offset)
+    }
+
+    fn encode(
+        &self,
+        value: U,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        Extractor::encode(
+            &self.inner,
+            (self.encode)(value),
+            path_offset,
+            request,
+            body,
+        )
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        Extractor::describe(&self.inner, operation, components)
+    }
+
+    fn compile(&self) {
+        Extractor::compile(&self.inner)
+    }
+}
+
+/// Outputs are mapped as a whole, so a single encoder and an HList of them
+/// can both be converted, e.g. into a struct via [`Encoder::map_to`].
+impl<E, T, U> Encoder for Mapped<E, T, U>
+where
+    E: Encodable<Input = T>,
+{
+    type Input = U;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure> {
+        self.inner.accepts(request)
+    }
+
+    fn encode(
+        &self,
+        value: U,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        self.inner.encode((self.encode)(value), request, response)
+    }
+
+    fn decode(&self, response: &response::Parts, body: &Bytes) -> Result<U, DecodeFailure> {
+        let value = self.inner.decode(response, body)?;
+        Ok((self.decode)(value))
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        self.inner.describe(response, components)
+    }
+}
+
+impl<E, T, U, Er> Encoder for TryMapped<E, T, U, Er>
+where
+    E: Encodable<Input = T>,
+    Er: Display,
+{
+    type Input = U;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure> {
+        self.inner.accepts(request)
+    }
+
+    fn encode(
+        &self,
+        value: U,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        self.inner.encode((self.encode)(value), request, response)
+    }
+
+    fn decode(&self, response: &response::Parts, body: &Bytes) -> Result<U, DecodeFailure> {
+        let value = self.inner.decode(response, body)?;
+        (self.decode)(value).map_err(|error| DecodeFailure::parse(DecodeInput::Mapped, error))
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        self.inner.describe(response, components)
+    }
+}
+
+#[async_trait]
+impl<In, T, U> Extractable for MappedInputs<In, T, U>
+where
+    In: Extractable<Output = T>,
+    T: Send,
+    U: Send,
+{
+    type Output = U;
+
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<U, DecodeFailure> {
+        let value = self.inputs.extract_from(path_offset, request, body).await?;
+        Ok((self.decode)(value))
+    }
+
+    async fn extract_all_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<U, DecodeFailures> {
+        let value = self
+            .inputs
+            .extract_all_from(path_offset, request, body)
+            .await?;
+        Ok((self.decode)(value))
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        self.inputs.path_segments()
+    }
+
+    fn encode_from(
+        &self,
+        value: U,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        self.inputs
+            .encode_from((self.encode)(value), path_offset, request, body)
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        self.inputs.describe(operation, components)
+    }
+
+    fn compile(&self) {
+        self.inputs.compile()
+    }
+}
+
+/// The conversion only runs once every input has decoded, so its failure is
+/// never reported alongside failures of the inputs themselves.
+#[async_trait]
+impl<In, T, U, Er> Extractable for TryMappedInputs<In, T, U, Er>
+where
+    In: Extractable<Output = T>,
+    T: Send,
+    U: Send,
+    Er: Display,
+{
+    type Output = U;
+
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<U, DecodeFailure> {
+        let value = self.inputs.extract_from(path_offset, request, body).await?;
+        (self.decode)(value).map_err(|error| DecodeFailure::parse(DecodeInput::Mapped, error))
+    }
+
+    async fn extract_all_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<U, DecodeFailures> {
+        let value = self
+            .inputs
+            .extract_all_from(path_offset, request, body)
+            .await?;
+        (self.decode)(value)
+            .map_err(|error| DecodeFailure::parse(DecodeInput::Mapped, error).into())
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        self.inputs.path_segments()
+    }
+
+    fn encode_from(
+        &self,
+        value: U,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        self.inputs
+            .encode_from((self.encode)(value), path_offset, request, body)
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        self.inputs.describe(operation, components)
+    }
+
+    fn compile(&self) {
+        self.inputs.compile()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, Request, StatusCode};
+    use frunk::{hlist, Generic};
+
+    use super::*;
+    use crate::decode::DecodeReason;
+    use crate::endpoint::endpoint;
+    use crate::extract::empty_endpoint;
+    use crate::output::{empty_output, response_header};
+    use crate::path::{path_capture, path_literal, PathCapture};
+    use crate::validate::{validated, Validated, Validator};
+
+    #[derive(Debug, PartialEq, Generic)]
+    struct ContactId(u64);
+
+    #[derive(Debug, PartialEq)]
+    struct Even(u64);
+
+    fn even(value: u64) -> Result<Even, String> {
+        match value % 2 {
+            0 => Ok(Even(value)),
+            _ => Err(format!("{} is odd", value)),
+        }
+    }
+
+    fn parts(uri: &str) -> Parts {
+        Request::builder().uri(uri).body(()).unwrap().into_parts().0
+    }
+
+    #[tokio::test]
+    async fn mapped_inputs_convert_both_ways() {
+        let inputs = empty_endpoint()
+            .with_extractor(path_literal("contacts"))
+            .with_extractor(path_capture::<u64>("id").map_to::<ContactId>());
+        assert_eq!(inputs.path_template(), "/contacts/{id}");
+
+        let mut request = parts("/");
+        let mut body = Bytes::new();
+        inputs
+            .encode(hlist![ContactId(7)], &mut request, &mut body)
+            .unwrap();
+        assert_eq!(request.uri, "/contacts/7");
+        let values = inputs.extract(&request, &body).await.unwrap();
+        assert_eq!(values, hlist![ContactId(7)]);
+    }
+
+    #[tokio::test]
+    async fn failed_conversions_are_reported_for_their_input() {
+        let id = path_capture::<u64>("id").try_map(even, |Even(value)| value);
+        assert_eq!(
+            Extractor::extract(&id, &parts("/8"), &Bytes::new())
+                .await
+                .unwrap(),
+            Even(8)
+        );
+        let failure = Extractor::extract(&id, &parts("/7"), &Bytes::new())
+            .await
+            .unwrap_err();
+        assert_eq!(failure.input, DecodeInput::PathSegment(0));
+        assert_eq!(failure.reason, DecodeReason::Parse("7 is odd".to_string()));
+        assert_eq!(failure.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn inputs_can_be_mapped_as_a_whole() {
+        let inputs = empty_endpoint()
+            .with_extractor(path_literal("contacts"))
+            .with_extractor(path_capture::<u64>("id"));
+        let mapped = inputs
+            .clone()
+            .map_inputs(|values| values.head, |id| hlist![id]);
+        assert_eq!(
+            mapped.extract(&parts("/contacts/7"), &Bytes::new()).await,
+            Ok(7)
+        );
+
+        let checked = inputs.try_map_inputs(|values| even(values.head), |Even(id)| hlist![id]);
+        let failures = checked
+            .extract_all(&parts("/contacts/7"), &Bytes::new())
+            .await
+            .unwrap_err();
+        let inputs: Vec<_> = failures.into_iter().map(|failure| failure.input).collect();
+        assert_eq!(inputs, [DecodeInput::Mapped]);
+    }
+
+    #[test]
+    fn mapped_outputs_convert_both_ways() {
+        let outputs =
+            empty_output().with_encoder(response_header::<u64>("x-id").map_to::<ContactId>());
+        let request = parts("/");
+        let mut response = Response::new(Body::empty());
+        outputs
+            .encode(hlist![ContactId(7)], &request, &mut response)
+            .unwrap();
+        assert_eq!(response.headers()["x-id"], "7");
+
+        let (response, _) = response.into_parts();
+        let decoded = outputs.decode(&response, &Bytes::new()).unwrap();
+        assert_eq!(decoded, hlist![ContactId(7)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid pattern `[a-`")]
+    fn mapped_inputs_are_prepared_with_their_endpoint() {
+        static NAME: Validated<PathCapture<String>> =
+            validated(path_capture("name"), &[Validator::Pattern("[a-")]);
+        endpoint(
+            Method::GET,
+            (&NAME).map(|name| name.len(), |len| "a".repeat(len)),
+        );
+    }
+}
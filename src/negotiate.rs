@@ -0,0 +1,386 @@
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+use axum::http::request::Parts;
+use axum::http::{response, Response};
+use hyper::body::Bytes;
+use serde_json::Value;
+
+use crate::codec::{BodyCodec, CodecError};
+use crate::decode::{DecodeFailure, DecodeInput, DecodeReason};
+use crate::encode::EncodeFailure;
+use crate::extract::Extractor;
+use crate::header::header_values;
+use crate::openapi::{ApiResponse, Components, MediaType, Operation};
+use crate::output::{set_body, Encoder};
+
+/// Returns the `type/subtype` part of a media type, lowercased and without
+/// parameters, e.g. `text/plain` for `text/plain; charset=utf-8`.
+pub fn essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Returns the essence of the request's `Content-Type`, if it has one.
+pub fn content_type(request: &Parts) -> Result<Option<String>, DecodeFailure> {
+    Ok(header_values(request, CONTENT_TYPE.as_str())?
+        .first()
+        .map(|media_type| essence(media_type)))
+}
+
+struct MediaRange {
+    essence: String,
+    q: f32,
+}
+
+fn parse_accept(accept: &str) -> Vec<MediaRange> {
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parameters = range.split(';');
+            let essence = parameters.next()?.trim().to_ascii_lowercase();
+            let q = parameters
+                .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                .map(|q| q.trim().parse::<f32>().ok())
+                .next()
+                .unwrap_or(Some(1.0))?;
+            Some(MediaRange { essence, q })
+        })
+        .filter(|range| !range.essence.is_empty())
+        .collect()
+}
+
+/// The quality of `media_type` under `ranges`, taken from the most specific
+/// matching range.
+fn quality(ranges: &[MediaRange], media_type: &str) -> f32 {
+    let essence = essence(media_type);
+    let wildcard = match essence.split_once('/') {
+        Some((main_type, _)) => format!("{}/*", main_type),
+        None => String::new(),
+    };
+    ranges
+        .iter()
+        .filter_map(|range| {
+            let specificity = if range.essence == essence {
+                2
+            } else if range.essence == wildcard {
+                1
+            } else if range.essence == "*/*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, range.q))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, q)| q)
+}
+
+/// Picks the media type to respond with from `offered`, ordered by the
+/// q-values of the `Accept` header and then by the order they are offered
+/// in. Without an `Accept` header, the first offered media type is used.
+pub fn negotiate<'a>(accept: Option<&str>, offered: &[&'a str]) -> Option<&'a str> {
+    let ranges = match accept {
+        Some(accept) if !accept.trim().is_empty() => parse_accept(accept),
+        _ => return offered.first().copied(),
+    };
+    let mut best: Option<(&'a str, f32)> = None;
+    for &media_type in offered {
+        let q = quality(&ranges, media_type);
+        let better = match best {
+            Some((_, best_q)) => q > best_q,
+            None => q > 0.0,
+        };
+        if better {
+            best = Some((media_type, q));
+        }
+    }
+    best.map(|(media_type, _)| media_type)
+}
+
+/// Negotiates the media type to respond with from the request's `Accept`
+/// header, failing with 406 Not Acceptable when none of `offered` is.
+pub fn accepted(request: &Parts, offered: &[&'static str]) -> Result<&'static str, DecodeFailure> {
+    let accept = header_values(request, ACCEPT.as_str())?.join(",");
+    let accept = Some(accept.as_str()).filter(|accept| !accept.is_empty());
+    negotiate(accept, offered).ok_or_else(|| {
+        DecodeFailure::new(
+            DecodeInput::Header(ACCEPT.to_string()),
+            DecodeReason::NotAcceptable(
+                offered
+                    .iter()
+                    .map(|media_type| media_type.to_string())
+                    .collect(),
+            ),
+        )
+    })
+}
+
+/// One codec of a [`OneOfBody`].
+pub struct BodyVariant<T> {
+    pub media_type: &'static str,
+    pub decode: fn(&Bytes) -> Result<T, CodecError>,
+    pub encode: fn(&T) -> Result<Bytes, CodecError>,
+    pub schema: fn(&mut Components) -> Value,
+    pub validate: fn(&T) -> Result<(), String>,
+    pub compile: fn(),
+}
+
+/// A body that can be represented in several media types, e.g. JSON and
+/// CBOR. As an input, the codec is chosen by the request's `Content-Type`;
+/// as an output, by its `Accept` header.
+pub struct OneOfBody<T> {
+    variants: Vec<BodyVariant<T>>,
+}
+
+impl<T> Clone for BodyVariant<T> {
+    fn clone(&self) -> Self {
+        BodyVariant { ..*self }
+    }
+}
+
+impl<T> Clone for OneOfBody<T> {
+    fn clone(&self) -> Self {
+        OneOfBody {
+            variants: self.variants.clone(),
+        }
+    }
+}
+
+pub fn one_of_body<C: BodyCodec>() -> OneOfBody<C::Value> {
+    OneOfBody {
+        variants: Vec::new(),
+    }
+    .or::<C>()
+}
+
+impl<T> OneOfBody<T> {
+    pub fn or<C: BodyCodec<Value = T>>(mut self) -> Self {
+        self.variants.push(BodyVariant {
+            media_type: C::MEDIA_TYPE,
+            decode: C::decode,
+            encode: C::encode,
+            schema: C::schema,
+            validate: C::validate,
+            compile: C::compile,
+        });
+        self
+    }
+
+    pub fn media_types(&self) -> Vec<&'static str> {
+        self.variants
+            .iter()
+            .map(|variant| variant.media_type)
+            .collect()
+    }
+
+    /// The variant whose media type matches `content_type`, or the first one
+    /// when there is no content type.
+    pub fn for_content_type(&self, content_type: Option<&str>) -> Option<&BodyVariant<T>> {
+        match content_type {
+            Some(content_type) => self
+                .variants
+                .iter()
+                .find(|variant| essence(variant.media_type) == essence(content_type)),
+            None => self.variants.first(),
+        }
+    }
+
+    /// The variant to respond with, negotiated from the request's `Accept`
+    /// header.
+    pub fn for_accept(&self, request: &Parts) -> Result<&BodyVariant<T>, DecodeFailure> {
+        let media_type = accepted(request, &self.media_types())?;
+        Ok(self
+            .variants
+            .iter()
+            .find(|variant| variant.media_type == media_type)
+            .expect("the accepted media type is one of the variants"))
+    }
+}
+
+#[async_trait]
+impl<T: Send> Extractor for OneOfBody<T> {
+    type Output = T;
+
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<T, DecodeFailure> {
+        let content_type = content_type(request)?;
+        let variant = self
+            .for_content_type(content_type.as_deref())
+            .ok_or_else(|| {
+                DecodeFailure::new(
+                    DecodeInput::Body,
+                    DecodeReason::UnsupportedMediaType(content_type.unwrap_or_default()),
+                )
+            })?;
+        let value = (variant.decode)(body)
+            .map_err(|error| DecodeFailure::deserialize(DecodeInput::Body, error))?;
+        (variant.validate)(&value)
+            .map_err(|rule| DecodeFailure::new(DecodeInput::Body, DecodeReason::Invalid(rule)))?;
+        Ok(value)
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Body
+    }
+
+    /// Requests are written with the first codec.
+    fn encode(
+        &self,
+        value: T,
+        _path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        let variant = self
+            .variants
+            .first()
+            .ok_or_else(|| EncodeFailure::new("body", "no codecs declared"))?;
+        (variant.validate)(&value).map_err(|rule| EncodeFailure::new("body", rule))?;
+        *body = (variant.encode)(&value).map_err(|error| EncodeFailure::new("body", error))?;
+        request
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static(variant.media_type));
+        Ok(())
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        for variant in &self.variants {
+            operation.request_content(variant.media_type, (variant.schema)(components));
+        }
+    }
+
+    fn compile(&self) {
+        for variant in &self.variants {
+            (variant.compile)();
+        }
+    }
+}
+
+impl<T> Encoder for OneOfBody<T> {
+    type Input = T;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure> {
+        self.for_accept(request).map(|_| ())
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        let variant = self
+            .for_accept(request)
+            .map_err(|failure| EncodeFailure::new("body", failure))?;
+        let body = (variant.encode)(&value).map_err(|error| EncodeFailure::new("body", error))?;
+        set_body(response, variant.media_type, body);
+        Ok(())
+    }
+
+    fn decode(&self, response: &response::Parts, body: &Bytes) -> Result<T, DecodeFailure> {
+        let content_type = response
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        let variant = self.for_content_type(content_type).ok_or_else(|| {
+            DecodeFailure::new(
+                DecodeInput::Body,
+                DecodeReason::UnsupportedMediaType(content_type.unwrap_or_default().to_string()),
+            )
+        })?;
+        (variant.decode)(body).map_err(|error| DecodeFailure::deserialize(DecodeInput::Body, error))
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        for variant in &self.variants {
+            response.content.insert(
+                variant.media_type.to_string(),
+                MediaType {
+                    schema: (variant.schema)(components),
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Request, StatusCode};
+
+    use super::*;
+    use crate::codec::{Json, Text};
+
+    #[test]
+    fn accept_ranges_are_parsed_with_q_values() {
+        let ranges = parse_accept("text/*;q=0.5, application/json, , */*; q=0.1");
+        let parsed: Vec<_> = ranges
+            .iter()
+            .map(|range| (range.essence.as_str(), range.q))
+            .collect();
+        assert_eq!(
+            parsed,
+            [("text/*", 0.5), ("application/json", 1.0), ("*/*", 0.1)]
+        );
+        assert!(parse_accept("text/plain;q=high").is_empty());
+    }
+
+    #[test]
+    fn the_most_specific_range_sets_the_quality() {
+        let ranges = parse_accept("*/*;q=0.1, text/*;q=0.5, text/plain;q=0");
+        assert_eq!(quality(&ranges, "text/plain; charset=utf-8"), 0.0);
+        assert_eq!(quality(&ranges, "text/html"), 0.5);
+        assert_eq!(quality(&ranges, "application/json"), 0.1);
+        assert_eq!(quality(&parse_accept("text/html"), "application/json"), 0.0);
+    }
+
+    #[test]
+    fn negotiation_prefers_q_values_then_offer_order() {
+        let offered = ["application/json", "application/cbor"];
+        assert_eq!(negotiate(None, &offered), Some("application/json"));
+        assert_eq!(
+            negotiate(Some("application/cbor, application/json;q=0.9"), &offered),
+            Some("application/cbor")
+        );
+        assert_eq!(negotiate(Some("*/*"), &offered), Some("application/json"));
+        assert_eq!(negotiate(Some("application/*;q=0"), &offered), None);
+        assert_eq!(negotiate(Some("text/plain"), &offered), None);
+    }
+
+    fn parts(header: &str, value: &str) -> Parts {
+        Request::builder()
+            .header(header, value)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[tokio::test]
+    async fn one_of_body_picks_the_codec_by_media_type() {
+        let body = one_of_body::<Json<String>>().or::<Text>();
+
+        let request = parts("content-type", "text/plain");
+        let text = Extractor::extract(&body, &request, &Bytes::from_static(b"hi")).await;
+        assert_eq!(text.unwrap(), "hi");
+        let request = parts("content-type", "application/json; charset=utf-8");
+        let json = Extractor::extract(&body, &request, &Bytes::from_static(b"\"hi\"")).await;
+        assert_eq!(json.unwrap(), "hi");
+
+        let request = parts("content-type", "application/xml");
+        let failure = Extractor::extract(&body, &request, &Bytes::from_static(b"<hi/>"))
+            .await
+            .unwrap_err();
+        assert_eq!(failure.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let request = parts("accept", "text/*");
+        let variant = body.for_accept(&request).ok().unwrap();
+        assert_eq!(variant.media_type, Text::MEDIA_TYPE);
+        let request = parts("accept", "application/xml");
+        let failure = body.for_accept(&request).err().unwrap();
+        assert_eq!(failure.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+}
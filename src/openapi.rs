@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::endpoint::Endpoint;
+use crate::error_output::ErrorOutput;
+use crate::extract::Extractable;
+use crate::output::Encodable;
+
+/// An OpenAPI 3.1 document, generated from endpoint definitions.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenApi {
+    pub openapi: String,
+    pub info: Info,
+    pub paths: BTreeMap<String, PathItem>,
+    #[serde(skip_serializing_if = "Components::is_empty")]
+    pub components: Components,
+}
+
+/// Definitions shared between operations, referred to via `$ref`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Components {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub schemas: BTreeMap<String, Value>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub security_schemes: BTreeMap<String, SecurityScheme>,
+    /// The Rust type registered under each schema name, to tell apart types
+    /// of the same name from different modules.
+    #[serde(skip)]
+    pub(crate) schema_types: BTreeMap<String, &'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Info {
+    pub title: String,
+    pub version: String,
+}
+
+/// The operations of one path, keyed by lowercase method.
+pub type PathItem = BTreeMap<String, Operation>;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Parameter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<RequestBody>,
+    pub responses: BTreeMap<String, ApiResponse>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub security: Vec<SecurityRequirement>,
+}
+
+/// The security schemes an operation requires, by name, with the scopes
+/// needed of each. Schemes without scopes, such as bearer tokens, need none.
+pub type SecurityRequirement = BTreeMap<String, Vec<String>>;
+
+/// How a request is authenticated, e.g. with an HTTP bearer token or an API
+/// key in a header.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityScheme {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The HTTP authentication scheme, e.g. `bearer`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    /// The name of the header, query parameter or cookie of an API key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "in", skip_serializing_if = "Option::is_none")]
+    pub location: Option<ParameterLocation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Parameter {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub location: ParameterLocation,
+    pub required: bool,
+    pub schema: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explode: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RequestBody {
+    pub content: BTreeMap<String, MediaType>,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaType {
+    pub schema: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApiResponse {
+    pub description: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, Header>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub content: BTreeMap<String, MediaType>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Header {
+    pub schema: Value,
+}
+
+impl Parameter {
+    pub fn new(
+        name: impl Into<String>,
+        location: ParameterLocation,
+        required: bool,
+        schema: Value,
+    ) -> Self {
+        Parameter {
+            name: name.into(),
+            location,
+            required,
+            schema,
+            style: None,
+            explode: None,
+        }
+    }
+}
+
+impl Components {
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty() && self.security_schemes.is_empty()
+    }
+}
+
+impl ApiResponse {
+    pub fn new(status: StatusCode) -> Self {
+        ApiResponse {
+            description: status.canonical_reason().unwrap_or("").to_string(),
+            ..ApiResponse::default()
+        }
+    }
+}
+
+impl Operation {
+    /// Adds `schema` as a request body in `media_type`.
+    pub fn request_content(&mut self, media_type: &str, schema: Value) {
+        self.request_body
+            .get_or_insert_with(|| RequestBody {
+                required: true,
+                ..RequestBody::default()
+            })
+            .content
+            .insert(media_type.to_string(), MediaType { schema });
+    }
+
+    /// Requires the security scheme `name`, registering `scheme` under it.
+    /// All schemes an operation requires go into one requirement, so a
+    /// request must satisfy each of them.
+    pub fn require_security(
+        &mut self,
+        components: &mut Components,
+        name: &str,
+        scheme: SecurityScheme,
+    ) {
+        components
+            .security_schemes
+            .entry(name.to_string())
+            .or_insert(scheme);
+        if self.security.is_empty() {
+            self.security.push(SecurityRequirement::new());
+        }
+        self.security[0].insert(name.to_string(), Vec::new());
+    }
+
+    /// The response documented for `status`, added if it is not yet.
+    pub fn response(&mut self, status: StatusCode) -> &mut ApiResponse {
+        self.responses
+            .entry(status.as_u16().to_string())
+            .or_insert_with(|| ApiResponse::new(status))
+    }
+}
+
+/// Starts an OpenAPI document; endpoints are added with
+/// [`OpenApi::endpoint`].
+pub fn openapi(title: impl Into<String>, version: impl Into<String>) -> OpenApi {
+    OpenApi {
+        openapi: "3.1.0".to_string(),
+        info: Info {
+            title: title.into(),
+            version: version.into(),
+        },
+        paths: BTreeMap::new(),
+        components: Components::default(),
+    }
+}
+
+/// A default operation id built from the method and path, e.g. `putHelloId`
+/// for `PUT /hello/{id}`.
+fn operation_id(method: &str, segments: &[String]) -> String {
+    let mut id = method.to_ascii_lowercase();
+    for segment in segments {
+        let mut characters = segment.chars().filter(|c| c.is_ascii_alphanumeric());
+        if let Some(first) = characters.next() {
+            id.push(first.to_ascii_uppercase());
+            id.extend(characters);
+        }
+    }
+    id
+}
+
+/// Documents an endpoint as a single operation, registering the schemas it
+/// refers to in `components`.
+pub fn operation<In, Err, Out, Sec>(
+    endpoint: &Endpoint<In, Err, Out, Sec>,
+    components: &mut Components,
+) -> Operation
+where
+    Sec: Extractable,
+    In: Extractable,
+    Err: ErrorOutput,
+    Out: Encodable,
+{
+    let mut operation = Operation {
+        operation_id: Some(endpoint.name.clone().unwrap_or_else(|| {
+            operation_id(endpoint.method.as_str(), &endpoint.input.path_segments())
+        })),
+        ..Operation::default()
+    };
+    endpoint.security_input.describe(&mut operation, components);
+    endpoint.input.describe(&mut operation, components);
+    endpoint
+        .output
+        .describe(operation.response(endpoint.status), components);
+    endpoint.error_output.describe(&mut operation, components);
+    operation
+}
+
+impl OpenApi {
+    pub fn endpoint<In, Err, Out, Sec>(mut self, endpoint: &Endpoint<In, Err, Out, Sec>) -> Self
+    where
+        Sec: Extractable,
+        In: Extractable,
+        Err: ErrorOutput,
+        Out: Encodable,
+    {
+        let operation = operation(endpoint, &mut self.components);
+        self.paths
+            .entry(endpoint.path_template())
+            .or_default()
+            .insert(endpoint.method.as_str().to_ascii_lowercase(), operation);
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("OpenAPI documents serialize to JSON")
+    }
+
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(self).expect("OpenAPI documents serialize to YAML")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Method;
+    use serde_json::json;
+
+    use super::*;
+    use crate::codec::Json;
+    use crate::endpoint::endpoint;
+    use crate::error_output::one_of_errors;
+    use crate::extract::{body, empty_endpoint};
+    use crate::header::optional_header;
+    use crate::output::{empty_output, response_body};
+    use crate::path::{path_capture, path_literal};
+    use crate::query::query_or;
+
+    #[test]
+    fn operation_ids_default_to_the_method_and_path() {
+        let segments = ["hello".to_string(), "{id}".to_string()];
+        assert_eq!(operation_id("PUT", &segments), "putHelloId");
+        assert_eq!(operation_id("GET", &[]), "get");
+    }
+
+    #[test]
+    fn endpoints_are_documented_as_operations() {
+        let hello = path_literal("hello");
+        let id = path_capture::<u64>("id");
+        let page = query_or("page", 1u32);
+        let request_id = optional_header::<String>("x-request-id");
+        let name = body::<Json<String>>();
+        let inputs = empty_endpoint()
+            .with_extractor(&hello)
+            .with_extractor(&id)
+            .with_extractor(&page)
+            .with_extractor(&request_id)
+            .with_extractor(&name);
+        let greeting = response_body::<Json<String>>();
+        let outputs = empty_output().with_encoder(&greeting);
+        let errors =
+            one_of_errors::<String>().variant::<Json<String>>(StatusCode::NOT_FOUND, |_| true);
+        let endpoint = endpoint(Method::PUT, inputs).output(outputs).errors(errors);
+
+        let document = serde_json::to_value(openapi("test", "1.0").endpoint(&endpoint)).unwrap();
+        let string = json!({ "type": "string" });
+        let json_string = json!({ "application/json": { "schema": string } });
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(
+            document["paths"]["/hello/{id}"]["put"],
+            json!({
+                "operationId": "putHelloId",
+                "parameters": [
+                    {
+                        "name": "id",
+                        "in": "path",
+                        "required": true,
+                        "schema": {
+                            "type": "integer",
+                            "format": "uint64",
+                            "minimum": 0,
+                            "maximum": u64::MAX,
+                        },
+                    },
+                    {
+                        "name": "page",
+                        "in": "query",
+                        "required": false,
+                        "schema": {
+                            "type": "integer",
+                            "format": "uint32",
+                            "minimum": 0,
+                            "maximum": u32::MAX,
+                            "default": 1,
+                        },
+                    },
+                    { "name": "x-request-id", "in": "header", "required": false, "schema": string },
+                ],
+                "requestBody": { "content": json_string, "required": true },
+                "responses": {
+                    "200": { "description": "OK", "content": json_string },
+                    "404": { "description": "Not Found", "content": json_string },
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::ops::Add;
+use std::str::FromStr;
+
+use axum::body::Body;
+use axum::http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use axum::http::request::Parts;
+use axum::http::{response, Response, StatusCode};
+use frunk::hlist::{HCons, HNil};
+use frunk::Generic;
+use hyper::body::Bytes;
+
+use crate::codec::BodyCodec;
+use crate::decode::{DecodeFailure, DecodeInput};
+use crate::encode::EncodeFailure;
+use crate::map::{Mapped, TryMapped};
+use crate::negotiate::accepted;
+use crate::openapi::{ApiResponse, Components, Header, MediaType};
+use crate::schema::Schema;
+
+/// This trait is used to write one value into a response.
+pub trait Encoder {
+    type Input;
+
+    /// Checks, before the server logic runs, that the request allows this
+    /// output, e.g. that its `Accept` header matches the body codec.
+    fn accepts(&self, _request: &Parts) -> Result<(), DecodeFailure> {
+        Ok(())
+    }
+
+    fn encode(
+        &self,
+        value: Self::Input,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure>;
+
+    /// Reads the value back from a response, the inverse of
+    /// [`Encoder::encode`].
+    fn decode(
+        &self,
+        response: &response::Parts,
+        body: &Bytes,
+    ) -> Result<Self::Input, DecodeFailure>;
+
+    /// Documents this output on the successful response.
+    fn describe(&self, _response: &mut ApiResponse, _components: &mut Components) {}
+
+    /// Converts a domain type into the encoded value, and back when decoding
+    /// a response, e.g. `response_header::<u64>("x-total").map(Total, |Total(total)| total)`.
+    fn map<U>(
+        self,
+        decode: fn(Self::Input) -> U,
+        encode: fn(U) -> Self::Input,
+    ) -> Mapped<Self, Self::Input, U>
+    where
+        Self: Sized,
+    {
+        Mapped::new(self, decode, encode)
+    }
+
+    /// Like [`Encoder::map`], for conversions from the encoded value that can
+    /// fail, which clients report as decode failures.
+    fn try_map<U, Er: Display>(
+        self,
+        decode: fn(Self::Input) -> Result<U, Er>,
+        encode: fn(U) -> Self::Input,
+    ) -> TryMapped<Self, Self::Input, U, Er>
+    where
+        Self: Sized,
+    {
+        TryMapped::new(self, decode, encode)
+    }
+
+    /// Converts a single-field struct deriving frunk's `Generic` into the
+    /// encoded value.
+    fn map_to<S>(self) -> Mapped<Self, Self::Input, S>
+    where
+        Self: Sized,
+        S: Generic<Repr = HCons<Self::Input, HNil>>,
+    {
+        self.map(
+            |value| {
+                frunk::from_generic(HCons {
+                    head: value,
+                    tail: HNil,
+                })
+            },
+            |value| frunk::into_generic(value).head,
+        )
+    }
+}
+
+/// Encoders can be shared by reference, e.g. when declared as `static`s.
+impl<E: Encoder + ?Sized> Encoder for &E {
+    type Input = E::Input;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure> {
+        Encoder::accepts(*self, request)
+    }
+
+    fn encode(
+        &self,
+        value: E::Input,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        Encoder::encode(*self, value, request, response)
+    }
+
+    fn decode(&self, response: &response::Parts, body: &Bytes) -> Result<E::Input, DecodeFailure> {
+        Encoder::decode(*self, response, body)
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        Encoder::describe(*self, response, components)
+    }
+}
+
+/// The response status code, chosen by the server logic.
+#[derive(Clone)]
+pub struct StatusOutput;
+
+/// A response header, written via [`Display`] and read via [`FromStr`].
+#[derive(Clone)]
+pub struct HeaderOutput<T> {
+    name: &'static str,
+    _input: PhantomData<fn(T)>,
+}
+
+/// The response body, encoded with the codec `C`.
+pub struct BodyOutput<C>(PhantomData<fn() -> C>);
+
+impl<C> Clone for BodyOutput<C> {
+    fn clone(&self) -> Self {
+        BodyOutput(PhantomData)
+    }
+}
+
+pub const fn status_code() -> StatusOutput {
+    StatusOutput
+}
+
+pub const fn response_header<T>(name: &'static str) -> HeaderOutput<T> {
+    HeaderOutput {
+        name,
+        _input: PhantomData,
+    }
+}
+
+pub const fn response_body<C: BodyCodec>() -> BodyOutput<C> {
+    BodyOutput(PhantomData)
+}
+
+/// Sets the body of `response` along with its `Content-Type`.
+pub fn set_body(response: &mut Response<Body>, media_type: &'static str, body: Bytes) {
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(media_type));
+    *response.body_mut() = Body::from(body);
+}
+
+impl Encoder for StatusOutput {
+    type Input = StatusCode;
+
+    fn encode(
+        &self,
+        value: StatusCode,
+        _request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        *response.status_mut() = value;
+        Ok(())
+    }
+
+    fn decode(
+        &self,
+        response: &response::Parts,
+        _body: &Bytes,
+    ) -> Result<StatusCode, DecodeFailure> {
+        Ok(response.status)
+    }
+}
+
+impl<T> Encoder for HeaderOutput<T>
+where
+    T: Display + FromStr + Schema,
+    T::Err: Display,
+{
+    type Input = T;
+
+    fn encode(
+        &self,
+        value: T,
+        _request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        let target = || format!("header `{}`", self.name);
+        let name = HeaderName::from_bytes(self.name.as_bytes())
+            .map_err(|error| EncodeFailure::new(target(), error))?;
+        let value = HeaderValue::from_str(&value.to_string())
+            .map_err(|error| EncodeFailure::new(target(), error))?;
+        response.headers_mut().append(name, value);
+        Ok(())
+    }
+
+    fn decode(&self, response: &response::Parts, _body: &Bytes) -> Result<T, DecodeFailure> {
+        let input = || DecodeInput::Header(self.name.to_string());
+        let value = response
+            .headers
+            .get(self.name)
+            .ok_or_else(|| DecodeFailure::missing(input()))?;
+        value
+            .to_str()
+            .map_err(|error| DecodeFailure::parse(input(), error))?
+            .parse::<T>()
+            .map_err(|error| DecodeFailure::parse(input(), error))
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        response.headers.insert(
+            self.name.to_string(),
+            Header {
+                schema: T::schema(components),
+            },
+        );
+    }
+}
+
+impl<C: BodyCodec> Encoder for BodyOutput<C> {
+    type Input = C::Value;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure> {
+        accepted(request, &[C::MEDIA_TYPE]).map(|_| ())
+    }
+
+    fn encode(
+        &self,
+        value: C::Value,
+        _request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        let body = C::encode(&value).map_err(|error| EncodeFailure::new("body", error))?;
+        set_body(response, C::MEDIA_TYPE, body);
+        Ok(())
+    }
+
+    fn decode(&self, _response: &response::Parts, body: &Bytes) -> Result<C::Value, DecodeFailure> {
+        C::decode(body).map_err(|error| DecodeFailure::deserialize(DecodeInput::Body, error))
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        response.content.insert(
+            C::MEDIA_TYPE.to_string(),
+            MediaType {
+                schema: C::schema(components),
+            },
+        );
+    }
+}
+
+pub fn empty_output() -> HNil {
+    HNil
+}
+
+/// This trait is used to write a list of values into a response.
+pub trait Encodable {
+    type Input;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure>;
+
+    fn encode(
+        &self,
+        value: Self::Input,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure>;
+
+    fn decode(
+        &self,
+        response: &response::Parts,
+        body: &Bytes,
+    ) -> Result<Self::Input, DecodeFailure>;
+
+    /// Documents all encoders on the successful response.
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components);
+
+    /// Encodes a struct whose generic representation is this output's HList.
+    fn encode_generic<S>(
+        &self,
+        value: S,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure>
+    where
+        S: Generic<Repr = Self::Input>,
+    {
+        self.encode(frunk::into_generic(value), request, response)
+    }
+
+    /// Adds an output after the ones already declared, owning it, so the
+    /// input HList is in declaration order. Encoders stored in a `static`
+    /// can be added by reference, e.g. `.with_encoder(&CONTACT_BODY)`.
+    fn with_encoder<E: Encoder>(self, encoder: E) -> <Self as Add<HCons<E, HNil>>>::Output
+    where
+        Self: Sized + Add<HCons<E, HNil>>,
+    {
+        self + HCons {
+            head: encoder,
+            tail: HNil,
+        }
+    }
+}
+
+impl<E: Encoder> Encodable for E {
+    type Input = E::Input;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure> {
+        Encoder::accepts(self, request)
+    }
+
+    fn encode(
+        &self,
+        value: Self::Input,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        Encoder::encode(self, value, request, response)
+    }
+
+    fn decode(
+        &self,
+        response: &response::Parts,
+        body: &Bytes,
+    ) -> Result<Self::Input, DecodeFailure> {
+        Encoder::decode(self, response, body)
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        Encoder::describe(self, response, components)
+    }
+}
+
+impl Encodable for HNil {
+    type Input = HNil;
+
+    fn accepts(&self, _: &Parts) -> Result<(), DecodeFailure> {
+        Ok(())
+    }
+
+    fn encode(&self, _: HNil, _: &Parts, _: &mut Response<Body>) -> Result<(), EncodeFailure> {
+        Ok(())
+    }
+
+    fn decode(&self, _: &response::Parts, _: &Bytes) -> Result<HNil, DecodeFailure> {
+        Ok(HNil)
+    }
+
+    fn describe(&self, _: &mut ApiResponse, _: &mut Components) {}
+}
+
+impl<E: Encoder, R: Encodable> Encodable for HCons<E, R> {
+    type Input = HCons<E::Input, R::Input>;
+
+    fn accepts(&self, request: &Parts) -> Result<(), DecodeFailure> {
+        Encoder::accepts(&self.head, request)?;
+        self.tail.accepts(request)
+    }
+
+    /// The head is declared before the tail, so it is encoded first.
+    fn encode(
+        &self,
+        value: Self::Input,
+        request: &Parts,
+        response: &mut Response<Body>,
+    ) -> Result<(), EncodeFailure> {
+        Encoder::encode(&self.head, value.head, request, response)?;
+        self.tail.encode(value.tail, request, response)
+    }
+
+    fn decode(
+        &self,
+        response: &response::Parts,
+        body: &Bytes,
+    ) -> Result<Self::Input, DecodeFailure> {
+        let head = Encoder::decode(&self.head, response, body)?;
+        let tail = self.tail.decode(response, body)?;
+        Ok(HCons { head, tail })
+    }
+
+    fn describe(&self, response: &mut ApiResponse, components: &mut Components) {
+        Encoder::describe(&self.head, response, components);
+        self.tail.describe(response, components);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+    use crate::codec::Json;
+
+    fn parts(accept: &str) -> Parts {
+        Request::builder()
+            .header("accept", accept)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[test]
+    fn body_outputs_check_the_accept_header() {
+        let body = response_body::<Json<String>>();
+        assert!(Encoder::accepts(&body, &parts("application/*")).is_ok());
+        let failure = Encoder::accepts(&body, &parts("application/xml")).unwrap_err();
+        assert_eq!(failure.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn outputs_can_be_owned_or_borrowed() {
+        static ECHO: HeaderOutput<String> = response_header("x-echo");
+        let outputs = empty_output()
+            .with_encoder(&ECHO)
+            .with_encoder(response_header::<u32>("x-count"));
+        let mut response = Response::new(Body::empty());
+        let value = HCons {
+            head: "hi".to_string(),
+            tail: HCons {
+                head: 3,
+                tail: HNil,
+            },
+        };
+        outputs.encode(value, &parts("*/*"), &mut response).unwrap();
+        assert_eq!(response.headers()["x-echo"], "hi");
+        assert_eq!(response.headers()["x-count"], "3");
+
+        let (response, _) = response.into_parts();
+        let decoded = outputs.decode(&response, &Bytes::new()).unwrap();
+        assert_eq!(decoded.head, "hi");
+        assert_eq!(decoded.tail.head, 3);
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use axum::http::request::Parts;
+use frunk::hlist::{HCons, HNil};
+use hyper::body::Bytes;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::decode::{DecodeFailure, DecodeFailures, DecodeInput, DecodeReason};
+use crate::encode::EncodeFailure;
+use crate::extract::{Extractable, Extractor};
+use crate::openapi::{Components, Operation, Parameter, ParameterLocation};
+use crate::schema::Schema;
+
+/// Characters percent-encoded when writing a path segment.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Matches a fixed path segment, such as `hello` in `/hello/{id}`.
+#[derive(Clone)]
+pub struct PathLiteral {
+    segment: &'static str,
+}
+
+/// Captures a path segment, percent-decoded and parsed via [`FromStr`].
+#[derive(Clone)]
+pub struct PathCapture<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+pub const fn path_literal(segment: &'static str) -> PathLiteral {
+    PathLiteral { segment }
+}
+
+pub const fn path_capture<T>(name: &'static str) -> PathCapture<T> {
+    PathCapture {
+        name,
+        _output: PhantomData,
+    }
+}
+
+/// Returns the non-empty path segment at `index`, counted from 0 after the
+/// leading `/`.
+pub fn path_segment(request: &Parts, index: usize) -> Result<&str, DecodeFailure> {
+    request
+        .uri
+        .path()
+        .trim_start_matches('/')
+        .split('/')
+        .nth(index)
+        .filter(|segment| !segment.is_empty())
+        .ok_or_else(|| DecodeFailure::missing(DecodeInput::PathSegment(index)))
+}
+
+fn decoded_path_segment(request: &Parts, index: usize) -> Result<String, DecodeFailure> {
+    let segment = path_segment(request, index)?;
+    percent_decode_str(segment)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|error| DecodeFailure::parse(DecodeInput::PathSegment(index), error))
+}
+
+/// Replaces the path and query of a request being built.
+pub fn set_uri(request: &mut Parts, path: &str, query: Option<&str>) -> Result<(), EncodeFailure> {
+    let uri = match query {
+        Some(query) if !query.is_empty() => format!("{}?{}", path, query),
+        _ => path.to_string(),
+    };
+    request.uri = uri
+        .parse()
+        .map_err(|error| EncodeFailure::new("uri", error))?;
+    Ok(())
+}
+
+/// Sets the path segment at `index` of a request being built, leaving any
+/// segments before it that are not set yet empty.
+pub fn set_path_segment(
+    request: &mut Parts,
+    index: usize,
+    value: &str,
+) -> Result<(), EncodeFailure> {
+    let path = request.uri.path().strip_prefix('/').unwrap_or_default();
+    let mut segments: Vec<String> = if path.is_empty() {
+        Vec::new()
+    } else {
+        path.split('/').map(str::to_string).collect()
+    };
+    if segments.len() <= index {
+        segments.resize(index + 1, String::new());
+    }
+    segments[index] = utf8_percent_encode(value, PATH_SEGMENT).to_string();
+    let query = request.uri.query().map(str::to_string);
+    set_uri(
+        request,
+        &format!("/{}", segments.join("/")),
+        query.as_deref(),
+    )
+}
+
+impl PathLiteral {
+    /// Checks that the path segment at `path_offset` is this literal.
+    fn check(&self, path_offset: usize, request: &Parts) -> Result<(), DecodeFailure> {
+        if decoded_path_segment(request, path_offset)? == self.segment {
+            Ok(())
+        } else {
+            Err(DecodeFailure::new(
+                DecodeInput::PathSegment(path_offset),
+                DecodeReason::Mismatch(self.segment.to_string()),
+            ))
+        }
+    }
+}
+
+/// Path literals are inputs without a value: they match their segment but
+/// add nothing to the output HList, so tuples, structs and handlers only see
+/// the values of the other inputs. Literals stored in a `static` can be
+/// added by reference.
+macro_rules! literal_input {
+    ($($lifetime:lifetime)?, $literal:ty) => {
+        #[async_trait]
+        impl<$($lifetime,)? R: Extractable> Extractable for HCons<$literal, R> {
+            type Output = R::Output;
+
+            async fn extract_from(
+                &self,
+                path_offset: usize,
+                request: &Parts,
+                body: &Bytes,
+            ) -> Result<R::Output, DecodeFailure> {
+                self.head.check(path_offset, request)?;
+                self.tail.extract_from(path_offset + 1, request, body).await
+            }
+
+            async fn extract_all_from(
+                &self,
+                path_offset: usize,
+                request: &Parts,
+                body: &Bytes,
+            ) -> Result<R::Output, DecodeFailures> {
+                let head = self.head.check(path_offset, request);
+                let tail = self.tail.extract_all_from(path_offset + 1, request, body).await;
+                match (head, tail) {
+                    (Ok(()), tail) => tail,
+                    (Err(failure), Ok(_)) => Err(failure.into()),
+                    (Err(failure), Err(mut failures)) => {
+                        failures.0.insert(0, failure);
+                        Err(failures)
+                    }
+                }
+            }
+
+            fn path_segments(&self) -> Vec<String> {
+                let mut segments = vec![self.head.segment.to_string()];
+                segments.extend(self.tail.path_segments());
+                segments
+            }
+
+            fn encode_from(
+                &self,
+                value: R::Output,
+                path_offset: usize,
+                request: &mut Parts,
+                body: &mut Bytes,
+            ) -> Result<(), EncodeFailure> {
+                set_path_segment(request, path_offset, self.head.segment)?;
+                self.tail.encode_from(value, path_offset + 1, request, body)
+            }
+
+            fn describe(&self, operation: &mut Operation, components: &mut Components) {
+                self.tail.describe(operation, components)
+            }
+
+            fn compile(&self) {
+                self.tail.compile()
+            }
+        }
+    };
+}
+
+literal_input!(, PathLiteral);
+literal_input!('a, &'a PathLiteral);
+
+/// A literal on its own is an input list without values, e.g. for
+/// `endpoint(Method::GET, path_literal("health"))`.
+#[async_trait]
+impl Extractable for PathLiteral {
+    type Output = HNil;
+
+    async fn extract_from(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        _body: &Bytes,
+    ) -> Result<HNil, DecodeFailure> {
+        self.check(path_offset, request).map(|()| HNil)
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        vec![self.segment.to_string()]
+    }
+
+    fn encode_from(
+        &self,
+        _: HNil,
+        path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        set_path_segment(request, path_offset, self.segment)
+    }
+
+    fn describe(&self, _: &mut Operation, _: &mut Components) {}
+
+    fn compile(&self) {}
+}
+
+#[async_trait]
+impl<T> Extractor for PathCapture<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = T;
+
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<T, DecodeFailure> {
+        self.extract_at(0, request, body).await
+    }
+
+    async fn extract_at(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        _body: &Bytes,
+    ) -> Result<T, DecodeFailure> {
+        decoded_path_segment(request, path_offset)?
+            .parse::<T>()
+            .map_err(|error| DecodeFailure::parse(DecodeInput::PathSegment(path_offset), error))
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        vec![format!("{{{}}}", self.name)]
+    }
+
+    fn input(&self, path_offset: usize) -> DecodeInput {
+        DecodeInput::PathSegment(path_offset)
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        set_path_segment(request, path_offset, &value.to_string())
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Path,
+            true,
+            T::schema(components),
+        ));
+    }
+}
+
+/// Whether `path` matches a path template given as segments, e.g. `hello`
+/// and `{id}` match `/hello/1337`.
+pub fn matches_template(segments: &[String], path: &str) -> bool {
+    let path = path.trim_start_matches('/');
+    let actual: Vec<&str> = if path.is_empty() {
+        Vec::new()
+    } else {
+        path.split('/').collect()
+    };
+    actual.len() == segments.len()
+        && segments.iter().zip(actual).all(|(segment, actual)| {
+            if segment.starts_with('{') {
+                !actual.is_empty()
+            } else {
+                segment == actual
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+    use crate::extract::{empty_endpoint, Extractable};
+
+    fn parts(uri: &str) -> Parts {
+        Request::builder().uri(uri).body(()).unwrap().into_parts().0
+    }
+
+    #[tokio::test]
+    async fn captures_are_percent_decoded_and_parsed() {
+        let hello = path_literal("hello");
+        let name = path_capture::<String>("name");
+        let id = path_capture::<u64>("id");
+        let inputs = empty_endpoint()
+            .with_extractor(&hello)
+            .with_extractor(&name)
+            .with_extractor(&id);
+        assert_eq!(inputs.path_template(), "/hello/{name}/{id}");
+
+        let request = parts("/hello/Jane%20Doe/42");
+        let values = inputs.extract(&request, &Bytes::new()).await.unwrap();
+        assert_eq!(values.head, "Jane Doe");
+        assert_eq!(values.tail.head, 42);
+    }
+
+    #[tokio::test]
+    async fn literals_and_captures_report_their_segment() {
+        let hello = path_literal("hello");
+        let id = path_capture::<u64>("id");
+        let inputs = empty_endpoint().with_extractor(&hello).with_extractor(&id);
+
+        let failures = inputs
+            .extract_all(&parts("/goodbye/x"), &Bytes::new())
+            .await
+            .unwrap_err();
+        let reasons: Vec<_> = failures.into_iter().collect();
+        assert_eq!(reasons[0].input, DecodeInput::PathSegment(0));
+        assert_eq!(
+            reasons[0].reason,
+            DecodeReason::Mismatch("hello".to_string())
+        );
+        assert_eq!(reasons[1].input, DecodeInput::PathSegment(1));
+        assert!(matches!(reasons[1].reason, DecodeReason::Parse(_)));
+
+        let failure = inputs
+            .extract(&parts("/hello"), &Bytes::new())
+            .await
+            .unwrap_err();
+        assert_eq!(failure, DecodeFailure::missing(DecodeInput::PathSegment(1)));
+    }
+
+    #[tokio::test]
+    async fn segments_are_percent_encoded_in_place() {
+        let mut request = parts("/?page=2");
+        set_path_segment(&mut request, 1, "Jane Doe/1?").unwrap();
+        assert_eq!(request.uri, "//Jane%20Doe%2F1%3F?page=2");
+        set_path_segment(&mut request, 0, "hello").unwrap();
+        assert_eq!(request.uri, "/hello/Jane%20Doe%2F1%3F?page=2");
+
+        let name = path_capture::<String>("name");
+        let value = Extractor::extract_at(&name, 1, &request, &Bytes::new()).await;
+        assert_eq!(value.unwrap(), "Jane Doe/1?");
+    }
+}
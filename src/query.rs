@@ -0,0 +1,423 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use axum::http::request::Parts;
+use hyper::body::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::decode::{DecodeFailure, DecodeInput};
+use crate::encode::EncodeFailure;
+use crate::extract::Extractor;
+use crate::openapi::{Components, Operation, Parameter, ParameterLocation};
+use crate::path::set_uri;
+use crate::schema::Schema;
+
+/// A required query parameter, parsed via [`FromStr`].
+#[derive(Clone)]
+pub struct QueryExtractor<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+/// An optional query parameter, extracted as `None` when absent.
+#[derive(Clone)]
+pub struct OptionalQueryExtractor<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+/// A query parameter that may be repeated, e.g. `?tags=a&tags=b`.
+#[derive(Clone)]
+pub struct RepeatedQueryExtractor<T> {
+    name: &'static str,
+    _output: PhantomData<fn() -> T>,
+}
+
+/// A query parameter that falls back to a default value when absent.
+#[derive(Clone)]
+pub struct DefaultQueryExtractor<T> {
+    name: &'static str,
+    default: T,
+}
+
+/// The whole query string, deserialized into a serde type.
+#[derive(Clone)]
+pub struct QueryStringExtractor<T> {
+    _output: PhantomData<fn() -> T>,
+}
+
+pub const fn query<T>(name: &'static str) -> QueryExtractor<T> {
+    QueryExtractor {
+        name,
+        _output: PhantomData,
+    }
+}
+
+pub const fn optional_query<T>(name: &'static str) -> OptionalQueryExtractor<T> {
+    OptionalQueryExtractor {
+        name,
+        _output: PhantomData,
+    }
+}
+
+pub const fn repeated_query<T>(name: &'static str) -> RepeatedQueryExtractor<T> {
+    RepeatedQueryExtractor {
+        name,
+        _output: PhantomData,
+    }
+}
+
+pub const fn query_or<T>(name: &'static str, default: T) -> DefaultQueryExtractor<T> {
+    DefaultQueryExtractor { name, default }
+}
+
+pub const fn query_string<T>() -> QueryStringExtractor<T> {
+    QueryStringExtractor {
+        _output: PhantomData,
+    }
+}
+
+/// Returns the percent-decoded values of every occurrence of the query
+/// parameter `name`, in order.
+pub fn query_values(request: &Parts, name: &str) -> Vec<String> {
+    let query = request.uri.query().unwrap_or("");
+    form_urlencoded::parse(query.as_bytes())
+        .filter(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .collect()
+}
+
+/// Appends already encoded `pairs` to the query string of a request being
+/// built.
+fn append_encoded_query(request: &mut Parts, pairs: &str) -> Result<(), EncodeFailure> {
+    let path = request.uri.path().to_string();
+    let query = match request.uri.query() {
+        Some(query) if !query.is_empty() && !pairs.is_empty() => format!("{}&{}", query, pairs),
+        Some(query) if !query.is_empty() => query.to_string(),
+        _ => pairs.to_string(),
+    };
+    set_uri(request, &path, Some(&query))
+}
+
+/// Appends the query parameter `name` to a request being built.
+pub fn append_query(request: &mut Parts, name: &str, value: &str) -> Result<(), EncodeFailure> {
+    let pair = form_urlencoded::Serializer::new(String::new())
+        .append_pair(name, value)
+        .finish();
+    append_encoded_query(request, &pair)
+}
+
+fn parse_query_value<T>(name: &str, value: &str) -> Result<T, DecodeFailure>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse::<T>()
+        .map_err(|error| DecodeFailure::parse(DecodeInput::Query(name.to_string()), error))
+}
+
+#[async_trait]
+impl<T> Extractor for QueryExtractor<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = T;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<T, DecodeFailure> {
+        match query_values(request, self.name).first() {
+            Some(value) => parse_query_value(self.name, value),
+            None => Err(DecodeFailure::missing(DecodeInput::Query(
+                self.name.to_string(),
+            ))),
+        }
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Query(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        append_query(request, self.name, &value.to_string())
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Query,
+            true,
+            T::schema(components),
+        ));
+    }
+}
+
+#[async_trait]
+impl<T> Extractor for OptionalQueryExtractor<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = Option<T>;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<Option<T>, DecodeFailure> {
+        query_values(request, self.name)
+            .first()
+            .map(|value| parse_query_value(self.name, value))
+            .transpose()
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Query(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: Option<T>,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        match value {
+            Some(value) => append_query(request, self.name, &value.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Query,
+            false,
+            T::schema(components),
+        ));
+    }
+}
+
+#[async_trait]
+impl<T> Extractor for RepeatedQueryExtractor<T>
+where
+    T: FromStr + Display + Schema + Send,
+    T::Err: Display,
+{
+    type Output = Vec<T>;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<Vec<T>, DecodeFailure> {
+        query_values(request, self.name)
+            .iter()
+            .map(|value| parse_query_value(self.name, value))
+            .collect()
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Query(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: Vec<T>,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        value
+            .iter()
+            .try_for_each(|value| append_query(request, self.name, &value.to_string()))
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        let mut parameter = Parameter::new(
+            self.name,
+            ParameterLocation::Query,
+            false,
+            Vec::<T>::schema(components),
+        );
+        parameter.explode = Some(true);
+        operation.parameters.push(parameter);
+    }
+}
+
+#[async_trait]
+impl<T> Extractor for DefaultQueryExtractor<T>
+where
+    T: FromStr + Display + Schema + Clone + Send + Sync,
+    T::Err: Display,
+{
+    type Output = T;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<T, DecodeFailure> {
+        match query_values(request, self.name).first() {
+            Some(value) => parse_query_value(self.name, value),
+            None => Ok(self.default.clone()),
+        }
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::Query(self.name.to_string())
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        append_query(request, self.name, &value.to_string())
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        let mut schema = T::schema(components);
+        // Non-string defaults such as numbers are written as JSON values.
+        let default = self.default.to_string();
+        let default = match schema["type"].as_str() {
+            Some("string") | None => Value::String(default),
+            Some(_) => serde_json::from_str(&default).unwrap_or(Value::String(default)),
+        };
+        schema["default"] = default;
+        operation.parameters.push(Parameter::new(
+            self.name,
+            ParameterLocation::Query,
+            false,
+            schema,
+        ));
+    }
+}
+
+#[async_trait]
+impl<T> Extractor for QueryStringExtractor<T>
+where
+    T: DeserializeOwned + Serialize + Schema + Send,
+{
+    type Output = T;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<T, DecodeFailure> {
+        serde_urlencoded::from_str(request.uri.query().unwrap_or(""))
+            .map_err(|error| DecodeFailure::deserialize(DecodeInput::QueryString, error))
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        DecodeInput::QueryString
+    }
+
+    fn encode(
+        &self,
+        value: T,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        let pairs = serde_urlencoded::to_string(&value)
+            .map_err(|error| EncodeFailure::new("query string", error))?;
+        append_encoded_query(request, &pairs)
+    }
+
+    /// Documented as a single object parameter whose properties are spread
+    /// over the query string.
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        let mut parameter = Parameter::new(
+            "query",
+            ParameterLocation::Query,
+            false,
+            T::schema(components),
+        );
+        parameter.style = Some("form".to_string());
+        parameter.explode = Some(true);
+        operation.parameters.push(parameter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::schema::Schema;
+
+    fn parts(uri: &str) -> Parts {
+        Request::builder().uri(uri).body(()).unwrap().into_parts().0
+    }
+
+    #[tokio::test]
+    async fn parameters_are_decoded_and_parsed() {
+        let request = parts("/contacts?name=Jane%20Doe&tags=a&tags=b&page=x");
+        let body = Bytes::new();
+
+        let name = Extractor::extract(&query::<String>("name"), &request, &body).await;
+        assert_eq!(name.unwrap(), "Jane Doe");
+        let tags = Extractor::extract(&repeated_query::<String>("tags"), &request, &body).await;
+        assert_eq!(tags.unwrap(), ["a", "b"]);
+        let limit = Extractor::extract(&optional_query::<u32>("limit"), &request, &body).await;
+        assert_eq!(limit.unwrap(), None);
+        let size = Extractor::extract(&query_or("size", 20u32), &request, &body).await;
+        assert_eq!(size.unwrap(), 20);
+
+        let page = Extractor::extract(&query::<u32>("page"), &request, &body).await;
+        assert_eq!(
+            page.unwrap_err().input,
+            DecodeInput::Query("page".to_string())
+        );
+        let missing = Extractor::extract(&query::<u32>("limit"), &request, &body).await;
+        assert_eq!(
+            missing.unwrap_err(),
+            DecodeFailure::missing(DecodeInput::Query("limit".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn query_strings_are_deserialized() {
+        #[derive(Debug, Deserialize, Serialize, PartialEq, Schema)]
+        struct Search {
+            name: String,
+            page: u32,
+        }
+
+        let request = parts("/contacts?name=Jane&page=2");
+        let search = Extractor::extract(&query_string::<Search>(), &request, &Bytes::new()).await;
+        assert_eq!(
+            search.unwrap(),
+            Search {
+                name: "Jane".to_string(),
+                page: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn appended_parameters_are_encoded_and_read_back() {
+        let mut request = parts("/contacts?page=2");
+        let mut body = Bytes::new();
+        let tags = repeated_query::<String>("tags");
+        Extractor::encode(
+            &tags,
+            vec!["a&b".to_string(), "c".to_string()],
+            0,
+            &mut request,
+            &mut body,
+        )
+        .unwrap();
+        Extractor::encode(
+            &optional_query::<u32>("limit"),
+            None,
+            0,
+            &mut request,
+            &mut body,
+        )
+        .unwrap();
+        assert_eq!(request.uri, "/contacts?page=2&tags=a%26b&tags=c");
+
+        let decoded = Extractor::extract(&tags, &request, &body).await;
+        assert_eq!(decoded.unwrap(), ["a&b", "c"]);
+    }
+}
@@ -0,0 +1,619 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde_json::{json, Map, Value};
+
+use crate::openapi::Components;
+
+pub use tapi_derive::Schema;
+
+/// The JSON Schema of a type, used to document inputs and outputs.
+///
+/// Types with a [`Schema::name`] are registered once as a component and
+/// referred to via `$ref`; all other types are inlined where they are used.
+/// Registering two different types under the same name panics.
+/// Implement it with `#[derive(Schema)]`, which follows the type's serde
+/// attributes.
+pub trait Schema {
+    /// The component name of this type, e.g. `Contact`.
+    fn name() -> Option<String> {
+        None
+    }
+
+    /// The full schema of this type, registering the components it refers to.
+    fn definition(components: &mut Components) -> Value;
+
+    /// Whether a field of this type may be left out of an object.
+    fn optional() -> bool {
+        false
+    }
+
+    /// Checks the rules declared with `#[validate(...)]` on the fields of
+    /// this value and of the values it holds, returning the first broken one,
+    /// e.g. `age: 12 is less than 18`.
+    fn validate_fields(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Prepares the rules of [`Schema::validate_fields`] when an endpoint is
+    /// built, panicking if one does not apply to its field. Types already in
+    /// `compiled` are skipped, so recursive types terminate.
+    fn compile_fields(_compiled: &mut BTreeSet<&'static str>) {}
+
+    /// The schema to use where this type occurs: a `$ref` to its component
+    /// if it has a name, or else its definition.
+    fn schema(components: &mut Components) -> Value {
+        let name = match Self::name() {
+            Some(name) => name,
+            None => return Self::definition(components),
+        };
+        let type_name = std::any::type_name::<Self>();
+        match components.schema_types.get(&name) {
+            Some(&registered) if registered != type_name => panic!(
+                "schema component `{}` is defined by both `{}` and `{}`, rename one of them with #[serde(rename)]",
+                name, registered, type_name
+            ),
+            Some(_) => {}
+            None => {
+                // Registered before the definition is built, so recursive
+                // types refer to themselves instead of recursing forever.
+                components.schema_types.insert(name.clone(), type_name);
+                components.schemas.insert(name.clone(), Value::Null);
+                let definition = Self::definition(components);
+                components.schemas.insert(name.clone(), definition);
+            }
+        }
+        json!({ "$ref": format!("#/components/schemas/{}", name) })
+    }
+}
+
+macro_rules! schema {
+    ($rust:ty, $schema:tt) => {
+        impl Schema for $rust {
+            fn definition(_: &mut Components) -> Value {
+                json!($schema)
+            }
+        }
+    };
+}
+
+schema!(bool, { "type": "boolean" });
+schema!(i8, { "type": "integer", "format": "int8", "minimum": i8::MIN, "maximum": i8::MAX });
+schema!(i16, { "type": "integer", "format": "int16", "minimum": i16::MIN, "maximum": i16::MAX });
+schema!(i32, { "type": "integer", "format": "int32", "minimum": i32::MIN, "maximum": i32::MAX });
+schema!(i64, { "type": "integer", "format": "int64", "minimum": i64::MIN, "maximum": i64::MAX });
+schema!(isize, { "type": "integer", "format": "int64", "minimum": isize::MIN, "maximum": isize::MAX });
+schema!(u8, { "type": "integer", "format": "uint8", "minimum": 0, "maximum": u8::MAX });
+schema!(u16, { "type": "integer", "format": "uint16", "minimum": 0, "maximum": u16::MAX });
+schema!(u32, { "type": "integer", "format": "uint32", "minimum": 0, "maximum": u32::MAX });
+schema!(u64, { "type": "integer", "format": "uint64", "minimum": 0, "maximum": u64::MAX });
+schema!(usize, { "type": "integer", "format": "uint64", "minimum": 0, "maximum": usize::MAX });
+schema!(f32, { "type": "number", "format": "float" });
+schema!(f64, { "type": "number", "format": "double" });
+schema!(char, { "type": "string", "minLength": 1, "maxLength": 1 });
+schema!(String, { "type": "string" });
+schema!(str, { "type": "string" });
+schema!((), { "type": "null" });
+
+/// Optional values are their inner type or `null`, which serde writes for
+/// `None`; as fields they are not required.
+impl<T: Schema> Schema for Option<T> {
+    fn definition(components: &mut Components) -> Value {
+        nullable(T::schema(components))
+    }
+
+    fn optional() -> bool {
+        true
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        match self {
+            Some(value) => value.validate_fields(),
+            None => Ok(()),
+        }
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        T::compile_fields(compiled)
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Box<T> {
+    fn name() -> Option<String> {
+        T::name()
+    }
+
+    fn definition(components: &mut Components) -> Value {
+        T::definition(components)
+    }
+
+    fn schema(components: &mut Components) -> Value {
+        T::schema(components)
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        (**self).validate_fields()
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        T::compile_fields(compiled)
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for &T {
+    fn name() -> Option<String> {
+        T::name()
+    }
+
+    fn definition(components: &mut Components) -> Value {
+        T::definition(components)
+    }
+
+    fn schema(components: &mut Components) -> Value {
+        T::schema(components)
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        (**self).validate_fields()
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        T::compile_fields(compiled)
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn definition(components: &mut Components) -> Value {
+        json!({ "type": "array", "items": T::schema(components) })
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        self.iter().try_for_each(|item| item.validate_fields())
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        T::compile_fields(compiled)
+    }
+}
+
+impl<T: Schema> Schema for [T] {
+    fn definition(components: &mut Components) -> Value {
+        json!({ "type": "array", "items": T::schema(components) })
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        self.iter().try_for_each(|item| item.validate_fields())
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        T::compile_fields(compiled)
+    }
+}
+
+impl<T: Schema, S> Schema for HashSet<T, S> {
+    fn definition(components: &mut Components) -> Value {
+        json!({ "type": "array", "items": T::schema(components), "uniqueItems": true })
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        self.iter().try_for_each(|item| item.validate_fields())
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        T::compile_fields(compiled)
+    }
+}
+
+impl<T: Schema> Schema for BTreeSet<T> {
+    fn definition(components: &mut Components) -> Value {
+        json!({ "type": "array", "items": T::schema(components), "uniqueItems": true })
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        self.iter().try_for_each(|item| item.validate_fields())
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        T::compile_fields(compiled)
+    }
+}
+
+/// Maps are objects whose keys are the map's keys, as serde writes them.
+impl<K, V: Schema, S> Schema for HashMap<K, V, S> {
+    fn definition(components: &mut Components) -> Value {
+        json!({ "type": "object", "additionalProperties": V::schema(components) })
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        self.values().try_for_each(|value| value.validate_fields())
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        V::compile_fields(compiled)
+    }
+}
+
+impl<K, V: Schema> Schema for BTreeMap<K, V> {
+    fn definition(components: &mut Components) -> Value {
+        json!({ "type": "object", "additionalProperties": V::schema(components) })
+    }
+
+    fn validate_fields(&self) -> Result<(), String> {
+        self.values().try_for_each(|value| value.validate_fields())
+    }
+
+    fn compile_fields(compiled: &mut BTreeSet<&'static str>) {
+        V::compile_fields(compiled)
+    }
+}
+
+/// Allows `null` in addition to `schema`: by adding it to the `type` of an
+/// inline schema, or else, e.g. for a `$ref`, via `oneOf`.
+pub fn nullable(mut schema: Value) -> Value {
+    let null_type = Value::from("null");
+    match schema.get_mut("type") {
+        Some(Value::String(kind)) if kind == "null" => {}
+        Some(Value::String(kind)) => {
+            let kind = Value::from(kind.as_str());
+            schema["type"] = json!([kind, null_type]);
+        }
+        Some(Value::Array(kinds)) => {
+            if !kinds.contains(&null_type) {
+                kinds.push(null_type);
+            }
+        }
+        _ => return json!({ "oneOf": [schema, { "type": "null" }] }),
+    }
+    schema
+}
+
+/// An object schema with the given properties, of which `required` must be
+/// present.
+pub fn object(properties: Map<String, Value>, required: Vec<Value>) -> Value {
+    let mut object = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        object["required"] = Value::Array(required);
+    }
+    object
+}
+
+/// Merges the definition of a `#[serde(flatten)]` field into the object
+/// schema `object`. Definitions that are not plain objects, such as enums,
+/// are added as an `allOf` constraint instead.
+pub fn flatten(object: &mut Value, flattened: Value) {
+    let properties = flattened.get("properties").and_then(Value::as_object);
+    match (object.as_object_mut(), properties) {
+        (Some(object), Some(properties)) => {
+            let target = object
+                .entry("properties")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(target) = target.as_object_mut() {
+                target.extend(properties.clone());
+            }
+            if let Some(required) = flattened.get("required").and_then(Value::as_array) {
+                let target = object
+                    .entry("required")
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Some(target) = target.as_array_mut() {
+                    target.extend(required.iter().cloned());
+                }
+            }
+        }
+        (Some(object), None) => {
+            let all_of = object
+                .entry("allOf")
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Some(all_of) = all_of.as_array_mut() {
+                all_of.push(flattened);
+            }
+        }
+        (None, _) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Deserialize, Serialize, Schema)]
+    #[serde(rename_all = "camelCase")]
+    struct Contact {
+        first_name: String,
+        #[serde(rename = "mail")]
+        email: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(skip)]
+        secret: u8,
+        #[serde(flatten)]
+        address: Address,
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    #[serde(tag = "kind")]
+    enum Event {
+        Created { id: u64 },
+        Deleted,
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    #[serde(tag = "kind", content = "data")]
+    enum Command {
+        Rename(String),
+        Clear,
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        Name(String),
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Status {
+        InProgress,
+        Done,
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    struct Tree {
+        children: Vec<Tree>,
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    #[serde(transparent)]
+    struct Page<T> {
+        items: Vec<T>,
+    }
+
+    #[test]
+    fn structs_follow_their_serde_attributes() {
+        let mut components = Components::default();
+        assert_eq!(
+            Contact::schema(&mut components),
+            json!({ "$ref": "#/components/schemas/Contact" })
+        );
+        let definition = &components.schemas["Contact"];
+        assert_eq!(
+            definition,
+            &json!({
+                "type": "object",
+                "properties": {
+                    "firstName": { "type": "string" },
+                    "mail": { "type": ["string", "null"] },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "city": { "type": "string" },
+                },
+                "required": ["firstName", "city"],
+            })
+        );
+
+        // The documented properties are the ones serde reads and writes.
+        let read: Contact =
+            serde_json::from_value(json!({ "firstName": "Ada", "city": "London", "secret": 1 }))
+                .unwrap();
+        assert_eq!(read.secret, 0);
+        let written = serde_json::to_value(read).unwrap();
+        let mut written: Vec<_> = written.as_object().unwrap().keys().collect();
+        let mut documented: Vec<_> = definition["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        written.sort();
+        documented.sort();
+        assert_eq!(written, documented);
+    }
+
+    #[test]
+    fn enums_follow_their_representation() {
+        let mut components = Components::default();
+        assert_eq!(
+            Event::definition(&mut components),
+            json!({
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": { "kind": { "const": "Created" }, "id": u64::schema(&mut components) },
+                        "required": ["kind", "id"],
+                    },
+                    {
+                        "type": "object",
+                        "properties": { "kind": { "const": "Deleted" } },
+                        "required": ["kind"],
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            Command::definition(&mut components),
+            json!({
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": { "kind": { "const": "Rename" }, "data": { "type": "string" } },
+                        "required": ["kind", "data"],
+                    },
+                    {
+                        "type": "object",
+                        "properties": { "kind": { "const": "Clear" } },
+                        "required": ["kind"],
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            Id::definition(&mut components),
+            json!({ "oneOf": [u64::schema(&mut components), { "type": "string" }] })
+        );
+        assert_eq!(
+            Status::definition(&mut components),
+            json!({ "type": "string", "enum": ["IN_PROGRESS", "DONE"] })
+        );
+    }
+
+    #[test]
+    fn named_types_are_registered_once_and_generic_ones_inlined() {
+        let mut components = Components::default();
+        let tree = json!({ "$ref": "#/components/schemas/Tree" });
+        assert_eq!(Tree::schema(&mut components), tree);
+        assert_eq!(
+            components.schemas["Tree"],
+            json!({
+                "type": "object",
+                "properties": { "children": { "type": "array", "items": tree } },
+                "required": ["children"],
+            })
+        );
+        assert_eq!(
+            Page::<Tree>::schema(&mut components),
+            json!({ "type": "array", "items": tree })
+        );
+        assert_eq!(components.schemas.len(), 1);
+    }
+
+    mod other {
+        use serde::Serialize;
+
+        use crate::schema::Schema;
+
+        #[derive(Serialize, Schema)]
+        pub struct Tree {
+            pub size: u32,
+        }
+    }
+
+    #[test]
+    fn wrappers_register_the_type_they_wrap() {
+        let mut components = Components::default();
+        Tree::schema(&mut components);
+        let tree = json!({ "$ref": "#/components/schemas/Tree" });
+        assert_eq!(<Box<Tree>>::schema(&mut components), tree);
+        assert_eq!(<&Tree>::schema(&mut components), tree);
+    }
+
+    #[test]
+    #[should_panic(expected = "schema component `Tree` is defined by both")]
+    fn types_of_the_same_name_are_rejected() {
+        let mut components = Components::default();
+        Tree::schema(&mut components);
+        other::Tree::schema(&mut components);
+    }
+
+    #[derive(Deserialize, Serialize, Schema)]
+    #[serde(rename_all = "camelCase")]
+    struct Person {
+        #[validate(min_length = 1, pattern = "^[A-Z]")]
+        first_name: String,
+        #[validate(minimum = 18, maximum = 120)]
+        age: u8,
+        #[validate(max_items = 2)]
+        friends: Vec<Person>,
+    }
+
+    fn person(age: u8) -> Person {
+        Person {
+            first_name: "Ada".to_string(),
+            age,
+            friends: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn field_rules_are_exported_into_the_schema() {
+        let mut components = Components::default();
+        assert_eq!(
+            Person::definition(&mut components),
+            json!({
+                "type": "object",
+                "properties": {
+                    "firstName": { "type": "string", "minLength": 1, "pattern": "^[A-Z]" },
+                    "age": { "type": "integer", "format": "uint8", "minimum": 18.0, "maximum": 120.0 },
+                    "friends": {
+                        "type": "array",
+                        "items": { "$ref": "#/components/schemas/Person" },
+                        "maxItems": 2,
+                    },
+                },
+                "required": ["firstName", "age", "friends"],
+            })
+        );
+    }
+
+    #[test]
+    fn field_rules_are_checked_on_nested_values() {
+        Person::compile_fields(&mut BTreeSet::new());
+        let mut ada = person(36);
+        assert_eq!(ada.validate_fields(), Ok(()));
+        ada.friends.push(person(12));
+        assert_eq!(
+            ada.validate_fields(),
+            Err("friends.age: 12 is less than 18".to_string())
+        );
+        ada.friends = vec![person(36), person(36), person(36)];
+        assert_eq!(
+            ada.validate_fields(),
+            Err("friends: has 3 items, more than 2".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "validator `MinLength(1)` does not apply to `u8`")]
+    fn field_rules_that_do_not_apply_are_rejected() {
+        #[derive(Serialize, Schema)]
+        struct Mismatched {
+            #[validate(min_length = 1)]
+            age: u8,
+        }
+        Mismatched::compile_fields(&mut BTreeSet::new());
+    }
+
+    #[test]
+    fn integers_are_bounded_by_their_type() {
+        let mut components = Components::default();
+        let schema = i32::schema(&mut components);
+        assert_eq!(schema["minimum"], i32::MIN);
+        assert_eq!(schema["maximum"], i32::MAX);
+        let schema = i64::schema(&mut components);
+        assert_eq!(schema["minimum"], i64::MIN);
+        assert_eq!(schema["maximum"], i64::MAX);
+        let schema = isize::schema(&mut components);
+        assert_eq!(schema["minimum"], isize::MIN as i64);
+        assert_eq!(schema["maximum"], isize::MAX as i64);
+    }
+
+    #[test]
+    fn options_allow_null() {
+        let mut components = Components::default();
+        assert_eq!(
+            Option::<String>::schema(&mut components),
+            json!({ "type": ["string", "null"] })
+        );
+        assert_eq!(
+            Option::<Option<String>>::schema(&mut components),
+            json!({ "type": ["string", "null"] })
+        );
+        assert_eq!(
+            Option::<()>::schema(&mut components),
+            json!({ "type": "null" })
+        );
+        assert_eq!(
+            Option::<Contact>::schema(&mut components),
+            json!({
+                "oneOf": [
+                    { "$ref": "#/components/schemas/Contact" },
+                    { "type": "null" },
+                ],
+            })
+        );
+        assert!(Option::<String>::optional());
+    }
+}
@@ -0,0 +1,343 @@
+use async_trait::async_trait;
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use hyper::body::Bytes;
+
+use crate::cookie::cookie;
+use crate::decode::{DecodeFailure, DecodeInput};
+use crate::encode::EncodeFailure;
+use crate::extract::Extractor;
+use crate::header::{append_header, header, header_values};
+use crate::openapi::{Components, Operation, ParameterLocation, SecurityScheme};
+use crate::query::query;
+
+/// A bearer token from the `Authorization` header, e.g.
+/// `Authorization: Bearer abc123`.
+#[derive(Clone)]
+pub struct BearerAuth;
+
+/// A username and password from the `Authorization` header, e.g.
+/// `Authorization: Basic YWRhOnNlY3JldA==`.
+#[derive(Clone)]
+pub struct BasicAuth;
+
+/// The credentials of HTTP basic authentication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// An API key in a header, query parameter or cookie.
+#[derive(Clone)]
+pub struct ApiKey {
+    name: &'static str,
+    location: ParameterLocation,
+}
+
+pub const fn bearer() -> BearerAuth {
+    BearerAuth
+}
+
+pub const fn basic() -> BasicAuth {
+    BasicAuth
+}
+
+pub const fn api_key_header(name: &'static str) -> ApiKey {
+    ApiKey {
+        name,
+        location: ParameterLocation::Header,
+    }
+}
+
+pub const fn api_key_query(name: &'static str) -> ApiKey {
+    ApiKey {
+        name,
+        location: ParameterLocation::Query,
+    }
+}
+
+pub const fn api_key_cookie(name: &'static str) -> ApiKey {
+    ApiKey {
+        name,
+        location: ParameterLocation::Cookie,
+    }
+}
+
+fn authorization_input() -> DecodeInput {
+    DecodeInput::Header(AUTHORIZATION.as_str().to_string())
+}
+
+/// Returns the credentials of the `Authorization` header, if it uses
+/// `scheme`. The scheme is matched case-insensitively.
+fn authorization<'a>(request: &'a Parts, scheme: &str) -> Result<&'a str, DecodeFailure> {
+    let value = header_values(request, AUTHORIZATION.as_str())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| DecodeFailure::missing(authorization_input()))?;
+    match value.split_once(' ') {
+        Some((found, credentials)) if found.eq_ignore_ascii_case(scheme) => Ok(credentials.trim()),
+        _ => Err(DecodeFailure::parse(
+            authorization_input(),
+            format!("expected the {} scheme", scheme),
+        )),
+    }
+}
+
+fn http_scheme(scheme: &str) -> SecurityScheme {
+    SecurityScheme {
+        kind: "http".to_string(),
+        scheme: Some(scheme.to_string()),
+        name: None,
+        location: None,
+    }
+}
+
+#[async_trait]
+impl Extractor for BearerAuth {
+    type Output = String;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<String, DecodeFailure> {
+        authorization(request, "Bearer").map(str::to_string)
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        authorization_input()
+    }
+
+    fn encode(
+        &self,
+        value: String,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        append_header(
+            request,
+            AUTHORIZATION.as_str(),
+            &format!("Bearer {}", value),
+        )
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.require_security(components, "bearerAuth", http_scheme("bearer"));
+    }
+}
+
+#[async_trait]
+impl Extractor for BasicAuth {
+    type Output = Credentials;
+
+    async fn extract(&self, request: &Parts, _body: &Bytes) -> Result<Credentials, DecodeFailure> {
+        let encoded = authorization(request, "Basic")?;
+        let decoded = STANDARD
+            .decode(encoded)
+            .map_err(|error| DecodeFailure::parse(authorization_input(), error))?;
+        let decoded = String::from_utf8(decoded)
+            .map_err(|error| DecodeFailure::parse(authorization_input(), error))?;
+        match decoded.split_once(':') {
+            Some((username, password)) => Ok(Credentials {
+                username: username.to_string(),
+                password: password.to_string(),
+            }),
+            None => Err(DecodeFailure::parse(
+                authorization_input(),
+                "expected `username:password`",
+            )),
+        }
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        authorization_input()
+    }
+
+    fn encode(
+        &self,
+        value: Credentials,
+        _path_offset: usize,
+        request: &mut Parts,
+        _body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        let encoded = STANDARD.encode(format!("{}:{}", value.username, value.password));
+        append_header(
+            request,
+            AUTHORIZATION.as_str(),
+            &format!("Basic {}", encoded),
+        )
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        operation.require_security(components, "basicAuth", http_scheme("basic"));
+    }
+}
+
+/// Reads and writes the key like the plain input at the same location, but
+/// documents it as a security scheme instead of a parameter.
+#[async_trait]
+impl Extractor for ApiKey {
+    type Output = String;
+
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<String, DecodeFailure> {
+        match self.location {
+            ParameterLocation::Query => {
+                Extractor::extract(&query::<String>(self.name), request, body).await
+            }
+            ParameterLocation::Cookie => {
+                Extractor::extract(&cookie::<String>(self.name), request, body).await
+            }
+            _ => Extractor::extract(&header::<String>(self.name), request, body).await,
+        }
+    }
+
+    fn input(&self, _path_offset: usize) -> DecodeInput {
+        match self.location {
+            ParameterLocation::Query => DecodeInput::Query(self.name.to_string()),
+            ParameterLocation::Cookie => DecodeInput::Cookie(self.name.to_string()),
+            _ => DecodeInput::Header(self.name.to_string()),
+        }
+    }
+
+    fn encode(
+        &self,
+        value: String,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        match self.location {
+            ParameterLocation::Query => Extractor::encode(
+                &query::<String>(self.name),
+                value,
+                path_offset,
+                request,
+                body,
+            ),
+            ParameterLocation::Cookie => Extractor::encode(
+                &cookie::<String>(self.name),
+                value,
+                path_offset,
+                request,
+                body,
+            ),
+            _ => Extractor::encode(
+                &header::<String>(self.name),
+                value,
+                path_offset,
+                request,
+                body,
+            ),
+        }
+    }
+
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        let scheme = SecurityScheme {
+            kind: "apiKey".to_string(),
+            scheme: None,
+            name: Some(self.name.to_string()),
+            location: Some(self.location),
+        };
+        operation.require_security(components, self.name, scheme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+    use crate::decode::DecodeReason;
+
+    fn empty() -> Parts {
+        Request::builder().body(()).unwrap().into_parts().0
+    }
+
+    /// Encodes `value` into an empty request and extracts it again.
+    async fn round_trip<E: Extractor>(extractor: &E, value: E::Output) -> (Parts, E::Output) {
+        let mut request = empty();
+        Extractor::encode(extractor, value, 0, &mut request, &mut Bytes::new()).unwrap();
+        let extracted = Extractor::extract(extractor, &request, &Bytes::new())
+            .await
+            .unwrap();
+        (request, extracted)
+    }
+
+    #[tokio::test]
+    async fn bearer_tokens_round_trip() {
+        let (request, token) = round_trip(&bearer(), "abc123".to_string()).await;
+        assert_eq!(request.headers[AUTHORIZATION], "Bearer abc123");
+        assert_eq!(token, "abc123");
+
+        let request = Request::builder()
+            .header("authorization", "bearer abc123")
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0;
+        let token = Extractor::extract(&bearer(), &request, &Bytes::new()).await;
+        assert_eq!(token.unwrap(), "abc123");
+    }
+
+    #[tokio::test]
+    async fn basic_credentials_round_trip() {
+        let credentials = Credentials {
+            username: "ada".to_string(),
+            password: "secret".to_string(),
+        };
+        let (request, extracted) = round_trip(&basic(), credentials.clone()).await;
+        assert_eq!(request.headers[AUTHORIZATION], "Basic YWRhOnNlY3JldA==");
+        assert_eq!(extracted, credentials);
+    }
+
+    #[tokio::test]
+    async fn other_schemes_and_missing_headers_fail() {
+        let failure = Extractor::extract(&bearer(), &empty(), &Bytes::new())
+            .await
+            .unwrap_err();
+        assert_eq!(failure, DecodeFailure::missing(authorization_input()));
+
+        let request = Request::builder()
+            .header("authorization", "Basic YWRhOnNlY3JldA==")
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0;
+        let failure = Extractor::extract(&bearer(), &request, &Bytes::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(failure.reason, DecodeReason::Parse(_)));
+    }
+
+    #[tokio::test]
+    async fn api_keys_are_read_where_they_are_declared() {
+        let (request, key) = round_trip(&api_key_header("x-api-key"), "k1".to_string()).await;
+        assert_eq!(request.headers["x-api-key"], "k1");
+        assert_eq!(key, "k1");
+        let (request, key) = round_trip(&api_key_query("api_key"), "k2".to_string()).await;
+        assert_eq!(request.uri, "/?api_key=k2");
+        assert_eq!(key, "k2");
+        let (request, key) = round_trip(&api_key_cookie("session"), "k3".to_string()).await;
+        assert_eq!(request.headers["cookie"], "session=k3");
+        assert_eq!(key, "k3");
+    }
+
+    #[test]
+    fn schemes_are_documented_as_security_requirements() {
+        let mut operation = Operation::default();
+        let mut components = Components::default();
+        Extractor::describe(&bearer(), &mut operation, &mut components);
+        Extractor::describe(
+            &api_key_header("x-api-key"),
+            &mut operation,
+            &mut components,
+        );
+        assert!(operation.parameters.is_empty());
+        assert_eq!(operation.security.len(), 1);
+        let names: Vec<_> = operation.security[0].keys().collect();
+        assert_eq!(names, ["bearerAuth", "x-api-key"]);
+        let scheme = &components.security_schemes["x-api-key"];
+        assert_eq!(scheme.kind, "apiKey");
+        assert_eq!(scheme.location, Some(ParameterLocation::Header));
+    }
+}
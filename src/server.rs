@@ -0,0 +1,598 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::header::{HeaderValue, CONTENT_TYPE};
+use axum::http::request::Parts;
+use axum::http::{Method, Request, Response, StatusCode};
+use axum::routing::{any, on, MethodFilter, MethodRouter};
+use axum::Router;
+use frunk::hlist::HCons;
+use http_body::{LengthLimitError, Limited};
+use hyper::body::Bytes;
+
+use crate::decode::DecodeFailures;
+use crate::docs::docs_routes;
+use crate::endpoint::Endpoint;
+use crate::error_output::ErrorOutput;
+use crate::extract::Extractable;
+use crate::handler::Handler;
+use crate::openapi::OpenApi;
+use crate::output::Encodable;
+
+/// The largest request body a server buffers, 2 MiB like axum's default.
+/// Requests with larger bodies are answered with 413 Payload Too Large.
+pub const BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// A plain text response, used when a request cannot be served.
+pub fn failure_response(status: StatusCode, message: impl ToString) -> Response<Body> {
+    let mut response = Response::new(Body::from(message.to_string()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    response
+}
+
+/// Buffers a request body of at most `limit` bytes, or returns the response
+/// to answer with instead.
+pub async fn buffer_body(body: Body, limit: usize) -> Result<Bytes, Response<Body>> {
+    hyper::body::to_bytes(Limited::new(body, limit))
+        .await
+        .map_err(|error| {
+            if error.is::<LengthLimitError>() {
+                failure_response(StatusCode::PAYLOAD_TOO_LARGE, error)
+            } else {
+                failure_response(StatusCode::BAD_REQUEST, error)
+            }
+        })
+}
+
+/// A plain text response listing every decode failure, one per line.
+fn failures_response(status: StatusCode, failures: DecodeFailures) -> Response<Body> {
+    let message = failures
+        .iter()
+        .map(|failure| failure.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    failure_response(status, message)
+}
+
+/// Decodes the regular inputs of a buffered request, checks the outputs are
+/// acceptable, runs `logic` and encodes its result.
+async fn serve_inputs<In, Err, Out, Sec, F, Fut>(
+    endpoint: &Endpoint<In, Err, Out, Sec>,
+    parts: &Parts,
+    bytes: &Bytes,
+    logic: F,
+) -> Response<Body>
+where
+    In: Extractable,
+    Err: ErrorOutput,
+    Out: Encodable,
+    F: FnOnce(In::Output) -> Fut,
+    Fut: Future<Output = Result<Out::Input, Err::Error>>,
+{
+    let input = match endpoint.input.extract_all(parts, bytes).await {
+        Ok(input) => input,
+        Err(failures) => return failures_response(failures.status(), failures),
+    };
+    if let Err(failure) = endpoint.output.accepts(parts) {
+        return failure_response(failure.status(), failure);
+    }
+    let result = logic(input).await;
+    match endpoint.encode_result(result, parts) {
+        Ok(response) => response,
+        Err(failure) => failure_response(failure.status(), failure),
+    }
+}
+
+/// Serves one request with an endpoint: decodes every input, checks the
+/// outputs are acceptable, runs the server logic and encodes its result.
+pub async fn handle<In, Err, Out, F, Fut>(
+    endpoint: &Endpoint<In, Err, Out>,
+    logic: &F,
+    request: Request<Body>,
+) -> Response<Body>
+where
+    In: Extractable,
+    Err: ErrorOutput,
+    Out: Encodable,
+    F: Fn(In::Output) -> Fut,
+    Fut: Future<Output = Result<Out::Input, Err::Error>>,
+{
+    let (parts, body) = request.into_parts();
+    let bytes = match buffer_body(body, BODY_LIMIT).await {
+        Ok(bytes) => bytes,
+        Err(response) => return response,
+    };
+    serve_inputs(endpoint, &parts, &bytes, logic).await
+}
+
+/// Serves one request with a secured endpoint. Only the security inputs are
+/// decoded first, answering `401 Unauthorized` if they are missing or
+/// malformed. The security logic then turns them into a principal, e.g. the
+/// authenticated user, or into one of the endpoint's errors, such as a
+/// `403 Forbidden` variant. Only then are the remaining inputs decoded and
+/// passed to the server logic together with the principal.
+pub async fn handle_secure<Sec, In, Err, Out, S, SFut, P, F, Fut>(
+    endpoint: &Endpoint<In, Err, Out, Sec>,
+    security_logic: &S,
+    logic: &F,
+    request: Request<Body>,
+) -> Response<Body>
+where
+    Sec: Extractable,
+    In: Extractable,
+    Err: ErrorOutput,
+    Out: Encodable,
+    S: Fn(Sec::Output) -> SFut,
+    SFut: Future<Output = Result<P, Err::Error>>,
+    F: Fn(P, In::Output) -> Fut,
+    Fut: Future<Output = Result<Out::Input, Err::Error>>,
+{
+    let (parts, body) = request.into_parts();
+    let bytes = match buffer_body(body, BODY_LIMIT).await {
+        Ok(bytes) => bytes,
+        Err(response) => return response,
+    };
+    let security = match endpoint.security_input.extract_all(&parts, &bytes).await {
+        Ok(security) => security,
+        Err(failures) => return failures_response(StatusCode::UNAUTHORIZED, failures),
+    };
+    let principal = match security_logic(security).await {
+        Ok(principal) => principal,
+        Err(error) => {
+            return match endpoint.encode_result(Err(error), &parts) {
+                Ok(response) => response,
+                Err(failure) => failure_response(failure.status(), failure),
+            }
+        }
+    };
+    serve_inputs(endpoint, &parts, &bytes, |input| logic(principal, input)).await
+}
+
+/// Renders a path template such as `/hello/{id}` in axum's `/hello/:id`
+/// syntax.
+pub fn axum_path(segments: &[String]) -> String {
+    let segments: Vec<String> = segments
+        .iter()
+        .map(|segment| match segment.strip_prefix('{') {
+            Some(name) => format!(":{}", name.trim_end_matches('}')),
+            None => segment.clone(),
+        })
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+/// Routes requests with `method` to `handler`, answering other methods with
+/// `405 Method Not Allowed`.
+fn method_router<H, Fut>(method: Method, handler: H) -> MethodRouter
+where
+    H: Fn(Request<Body>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    match MethodFilter::try_from(method.clone()) {
+        Ok(filter) => on(filter, handler),
+        // axum has no filter for some methods, e.g. CONNECT, so those are
+        // matched here instead.
+        Err(_) => any(move |request: Request<Body>| {
+            let response = (request.method() == method).then(|| handler(request));
+            async move {
+                match response {
+                    Some(response) => response.await,
+                    None => failure_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"),
+                }
+            }
+        }),
+    }
+}
+
+/// Turns an endpoint and its server logic into a router with a single route.
+pub fn route<In, Err, Out, F, Fut>(
+    endpoint: impl Into<Arc<Endpoint<In, Err, Out>>>,
+    logic: F,
+) -> Router
+where
+    In: Extractable + Send + 'static,
+    Err: ErrorOutput + Send + Sync + 'static,
+    Out: Encodable + Send + Sync + 'static,
+    F: Fn(In::Output) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+{
+    let endpoint = endpoint.into();
+    let path = axum_path(&endpoint.input.path_segments());
+    let method = endpoint.method.clone();
+    let logic = Arc::new(logic);
+    let handler = move |request: Request<Body>| {
+        let endpoint = endpoint.clone();
+        let logic = logic.clone();
+        async move { handle(&*endpoint, &*logic, request).await }
+    };
+    Router::new().route(&path, method_router(method, handler))
+}
+
+/// Like [`route`], for an endpoint with security inputs, which are handed to
+/// `security_logic` before `logic` runs; see [`handle_secure`].
+pub fn route_secure<Sec, In, Err, Out, S, SFut, P, F, Fut>(
+    endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+    security_logic: S,
+    logic: F,
+) -> Router
+where
+    Sec: Extractable + Send + 'static,
+    In: Extractable + Send + 'static,
+    Err: ErrorOutput + Send + Sync + 'static,
+    Out: Encodable + Send + Sync + 'static,
+    S: Fn(Sec::Output) -> SFut + Send + Sync + 'static,
+    SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+    P: Send + 'static,
+    F: Fn(P, In::Output) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+{
+    let endpoint = endpoint.into();
+    let path = axum_path(&endpoint.input.path_segments());
+    let method = endpoint.method.clone();
+    let logic = Arc::new((security_logic, logic));
+    let handler = move |request: Request<Body>| {
+        let endpoint = endpoint.clone();
+        let logic = logic.clone();
+        async move { handle_secure(&*endpoint, &logic.0, &logic.1, request).await }
+    };
+    Router::new().route(&path, method_router(method, handler))
+}
+
+/// Adds endpoints to a router, so several endpoints can be served together:
+/// `Router::new().endpoint(get_contact, get).endpoint(update_contact, update)`,
+/// along with their documentation: `.docs(&document, "/docs")`.
+pub trait RouterExt {
+    fn endpoint<In, Err, Out, F, Fut>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out>>>,
+        logic: F,
+    ) -> Self
+    where
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        F: Fn(In::Output) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static;
+
+    /// Adds an endpoint with security inputs, see [`route_secure`].
+    fn secure_endpoint<Sec, In, Err, Out, S, SFut, P, F, Fut>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+        security_logic: S,
+        logic: F,
+    ) -> Self
+    where
+        Sec: Extractable + Send + 'static,
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        S: Fn(Sec::Output) -> SFut + Send + Sync + 'static,
+        SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+        P: Send + 'static,
+        F: Fn(P, In::Output) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static;
+
+    /// Adds an endpoint served by a plain async function taking one argument
+    /// per input, e.g. `.handler(update_contact, update_contact_logic)` with
+    /// `async fn update_contact_logic(id: u64, contact: Contact)`.
+    fn handler<In, Err, Out, H>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out>>>,
+        handler: H,
+    ) -> Self
+    where
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        H: Handler<In::Output, Result<Out::Input, Err::Error>> + Send + Sync + 'static,
+        H::Future: Send + 'static;
+
+    /// Like [`RouterExt::handler`], for an endpoint with security inputs. The
+    /// principal returned by `security_logic` is the handler's first argument.
+    fn secure_handler<Sec, In, Err, Out, S, SFut, P, H>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+        security_logic: S,
+        handler: H,
+    ) -> Self
+    where
+        Sec: Extractable + Send + 'static,
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        S: Fn(Sec::Output) -> SFut + Send + Sync + 'static,
+        SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+        P: Send + 'static,
+        H: Handler<HCons<P, In::Output>, Result<Out::Input, Err::Error>> + Send + Sync + 'static,
+        H::Future: Send + 'static;
+
+    /// Serves `document` and Swagger UI under `mount_path`, see
+    /// [`docs_routes`].
+    fn docs(self, document: &OpenApi, mount_path: &str) -> Self;
+}
+
+impl RouterExt for Router {
+    fn endpoint<In, Err, Out, F, Fut>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out>>>,
+        logic: F,
+    ) -> Self
+    where
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        F: Fn(In::Output) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+    {
+        self.merge(route(endpoint, logic))
+    }
+
+    fn secure_endpoint<Sec, In, Err, Out, S, SFut, P, F, Fut>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+        security_logic: S,
+        logic: F,
+    ) -> Self
+    where
+        Sec: Extractable + Send + 'static,
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        S: Fn(Sec::Output) -> SFut + Send + Sync + 'static,
+        SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+        P: Send + 'static,
+        F: Fn(P, In::Output) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+    {
+        self.merge(route_secure(endpoint, security_logic, logic))
+    }
+
+    fn handler<In, Err, Out, H>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out>>>,
+        handler: H,
+    ) -> Self
+    where
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        H: Handler<In::Output, Result<Out::Input, Err::Error>> + Send + Sync + 'static,
+        H::Future: Send + 'static,
+    {
+        self.endpoint(endpoint, move |input| handler.call(input))
+    }
+
+    fn secure_handler<Sec, In, Err, Out, S, SFut, P, H>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+        security_logic: S,
+        handler: H,
+    ) -> Self
+    where
+        Sec: Extractable + Send + 'static,
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        S: Fn(Sec::Output) -> SFut + Send + Sync + 'static,
+        SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+        P: Send + 'static,
+        H: Handler<HCons<P, In::Output>, Result<Out::Input, Err::Error>> + Send + Sync + 'static,
+        H::Future: Send + 'static,
+    {
+        self.secure_endpoint(endpoint, security_logic, move |principal, input| {
+            handler.call(HCons {
+                head: principal,
+                tail: input,
+            })
+        })
+    }
+
+    fn docs(self, document: &OpenApi, mount_path: &str) -> Self {
+        self.merge(docs_routes(document, mount_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use frunk::{hlist, HList};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::codec::Json;
+    use crate::endpoint::endpoint;
+    use crate::error_output::one_of_errors;
+    use crate::extract::empty_endpoint;
+    use crate::output::{empty_output, response_body, BodyOutput};
+    use crate::path::{path_capture, path_literal, PathCapture, PathLiteral};
+    use crate::security::bearer;
+
+    static CONTACTS: PathLiteral = path_literal("contacts");
+
+    static ID: PathCapture<u64> = path_capture("id");
+
+    static NAME: BodyOutput<Json<String>> = response_body();
+
+    async fn get_contact(input: HList![u64]) -> Result<HList![String], std::convert::Infallible> {
+        Ok(hlist![format!("contact {}", input.head)])
+    }
+
+    fn app(method: Method) -> Router {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        route(endpoint(method, inputs).output(outputs), get_contact)
+    }
+
+    async fn send(app: Router, method: Method, uri: &str, accept: &str) -> (StatusCode, Bytes) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("accept", accept)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        (
+            status,
+            hyper::body::to_bytes(response.into_body()).await.unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn routes_serve_their_endpoint() {
+        let (status, body) = send(app(Method::GET), Method::GET, "/contacts/7", "*/*").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "\"contact 7\"");
+
+        let (status, _) = send(app(Method::GET), Method::GET, "/contacts/x", "*/*").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(app(Method::GET), Method::GET, "/users/7", "*/*").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(app(Method::GET), Method::POST, "/contacts/7", "*/*").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        let (status, _) = send(
+            app(Method::GET),
+            Method::GET,
+            "/contacts/7",
+            "application/xml",
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[tokio::test]
+    async fn shared_endpoints_can_be_routed_several_times() {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(response_body::<Json<String>>());
+        let shared = Arc::new(endpoint(Method::GET, inputs).output(outputs));
+        for app in [
+            route(shared.clone(), get_contact),
+            route(shared, get_contact),
+        ] {
+            let (status, body) = send(app, Method::GET, "/contacts/7", "*/*").await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body, "\"contact 7\"");
+        }
+    }
+
+    async fn get_contact_by_id(id: u64) -> Result<HList![String], std::convert::Infallible> {
+        Ok(hlist![format!("contact {}", id)])
+    }
+
+    #[tokio::test]
+    async fn handlers_take_no_arguments_for_path_literals() {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        let app = Router::new().handler(
+            endpoint(Method::GET, inputs).output(outputs),
+            get_contact_by_id,
+        );
+        let (status, body) = send(app, Method::GET, "/contacts/7", "*/*").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "\"contact 7\"");
+    }
+
+    #[tokio::test]
+    async fn methods_without_an_axum_filter_are_routed() {
+        let (status, _) = send(app(Method::CONNECT), Method::CONNECT, "/contacts/7", "*/*").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(app(Method::CONNECT), Method::GET, "/contacts/7", "*/*").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn bodies_over_the_limit_are_rejected() {
+        let bytes = buffer_body(Body::from("0123456789"), 10).await.unwrap();
+        assert_eq!(bytes.len(), 10);
+        let response = buffer_body(Body::from("0123456789a"), 10)
+            .await
+            .unwrap_err();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    async fn authenticate(token: String) -> Result<String, String> {
+        match token.as_str() {
+            "secret" => Ok("ada".to_string()),
+            _ => Err("unknown token".to_string()),
+        }
+    }
+
+    async fn get_own_contact(user: String, input: HList![u64]) -> Result<HList![String], String> {
+        Ok(hlist![format!("contact {} of {}", input.head, user)])
+    }
+
+    fn secure_app(method: Method) -> Router {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        let errors =
+            one_of_errors::<String>().variant::<Json<String>>(StatusCode::FORBIDDEN, |_| true);
+        let endpoint = endpoint(method, inputs)
+            .security_input(bearer())
+            .output(outputs)
+            .errors(errors);
+        route_secure(endpoint, authenticate, get_own_contact)
+    }
+
+    async fn send_secure(
+        app: Router,
+        method: Method,
+        token: Option<&str>,
+        body: Body,
+    ) -> StatusCode {
+        let mut request = Request::builder().method(method).uri("/contacts/7");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        let request = request.body(body).unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn secure_routes_authenticate_before_serving() {
+        let status = send_secure(secure_app(Method::GET), Method::GET, None, Body::empty()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status = send_secure(
+            secure_app(Method::GET),
+            Method::GET,
+            Some("guess"),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let status = send_secure(
+            secure_app(Method::GET),
+            Method::GET,
+            Some("secret"),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let status = send_secure(
+            secure_app(Method::CONNECT),
+            Method::CONNECT,
+            Some("secret"),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn secure_routes_limit_bodies_before_authenticating() {
+        let body = Body::from(vec![0; BODY_LIMIT + 1]);
+        let status = send_secure(secure_app(Method::GET), Method::GET, None, body).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,388 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use axum::http::{Method, Request, Response, StatusCode};
+use frunk::hlist::HCons;
+use tower::make::Shared;
+use tower::Service;
+
+use crate::endpoint::Endpoint;
+use crate::error_output::ErrorOutput;
+use crate::extract::Extractable;
+use crate::handler::Handler;
+use crate::output::Encodable;
+use crate::path::matches_template;
+use crate::server::{failure_response, handle, handle_secure};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// An endpoint together with its server logic, with the types erased so
+/// that endpoints of different shapes can be served side by side.
+trait ServerEndpoint: Send + Sync {
+    fn method(&self) -> &Method;
+    fn path_segments(&self) -> &[String];
+    fn handle(&self, request: Request<Body>) -> BoxFuture<'_, Response<Body>>;
+}
+
+struct Served<In, Err, Out, F> {
+    endpoint: Arc<Endpoint<In, Err, Out>>,
+    logic: F,
+    path_segments: Vec<String>,
+}
+
+impl<In, Err, Out, F, Fut> ServerEndpoint for Served<In, Err, Out, F>
+where
+    In: Extractable + Send,
+    Err: ErrorOutput + Send + Sync,
+    Out: Encodable + Send + Sync,
+    F: Fn(In::Output) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+{
+    fn method(&self) -> &Method {
+        &self.endpoint.method
+    }
+
+    fn path_segments(&self) -> &[String] {
+        &self.path_segments
+    }
+
+    fn handle(&self, request: Request<Body>) -> BoxFuture<'_, Response<Body>> {
+        Box::pin(handle(&*self.endpoint, &self.logic, request))
+    }
+}
+
+struct SecureServed<In, Err, Out, Sec, S, F> {
+    endpoint: Arc<Endpoint<In, Err, Out, Sec>>,
+    security_logic: S,
+    logic: F,
+    path_segments: Vec<String>,
+}
+
+impl<Sec, In, Err, Out, S, SFut, P, F, Fut> ServerEndpoint for SecureServed<In, Err, Out, Sec, S, F>
+where
+    Sec: Extractable + Send,
+    In: Extractable + Send,
+    Err: ErrorOutput + Send + Sync,
+    Out: Encodable + Send + Sync,
+    S: Fn(Sec::Output) -> SFut + Send + Sync,
+    SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+    P: Send + 'static,
+    F: Fn(P, In::Output) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+{
+    fn method(&self) -> &Method {
+        &self.endpoint.method
+    }
+
+    fn path_segments(&self) -> &[String] {
+        &self.path_segments
+    }
+
+    fn handle(&self, request: Request<Body>) -> BoxFuture<'_, Response<Body>> {
+        Box::pin(handle_secure(
+            &*self.endpoint,
+            &self.security_logic,
+            &self.logic,
+            request,
+        ))
+    }
+}
+
+/// A [`tower::Service`] serving a set of endpoints, for use with plain hyper
+/// servers or tower stacks instead of axum's router. Clones share their
+/// endpoints until one of them adds another.
+#[derive(Clone, Default)]
+pub struct EndpointService {
+    endpoints: Arc<Vec<Arc<dyn ServerEndpoint>>>,
+}
+
+impl EndpointService {
+    pub fn new() -> Self {
+        EndpointService::default()
+    }
+
+    pub fn endpoint<In, Err, Out, F, Fut>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out>>>,
+        logic: F,
+    ) -> Self
+    where
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        F: Fn(In::Output) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+    {
+        let endpoint = endpoint.into();
+        let path_segments = endpoint.input.path_segments();
+        self.serve(Arc::new(Served {
+            endpoint,
+            logic,
+            path_segments,
+        }))
+    }
+
+    /// Adds an endpoint with security inputs, which are handed to
+    /// `security_logic` before `logic` runs, see
+    /// [`handle_secure`](crate::server::handle_secure).
+    pub fn secure_endpoint<Sec, In, Err, Out, S, SFut, P, F, Fut>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+        security_logic: S,
+        logic: F,
+    ) -> Self
+    where
+        Sec: Extractable + Send + 'static,
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        S: Fn(Sec::Output) -> SFut + Send + Sync + 'static,
+        SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+        P: Send + 'static,
+        F: Fn(P, In::Output) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out::Input, Err::Error>> + Send + 'static,
+    {
+        let endpoint = endpoint.into();
+        let path_segments = endpoint.input.path_segments();
+        self.serve(Arc::new(SecureServed {
+            endpoint,
+            security_logic,
+            logic,
+            path_segments,
+        }))
+    }
+
+    /// Adds an endpoint served by a plain async function taking one argument
+    /// per input, see [`Handler`].
+    pub fn handler<In, Err, Out, H>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out>>>,
+        handler: H,
+    ) -> Self
+    where
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        H: Handler<In::Output, Result<Out::Input, Err::Error>> + Send + Sync + 'static,
+        H::Future: Send + 'static,
+    {
+        self.endpoint(endpoint, move |input| handler.call(input))
+    }
+
+    /// Like [`EndpointService::handler`], for an endpoint with security
+    /// inputs. The principal is the handler's first argument.
+    pub fn secure_handler<Sec, In, Err, Out, S, SFut, P, H>(
+        self,
+        endpoint: impl Into<Arc<Endpoint<In, Err, Out, Sec>>>,
+        security_logic: S,
+        handler: H,
+    ) -> Self
+    where
+        Sec: Extractable + Send + 'static,
+        In: Extractable + Send + 'static,
+        Err: ErrorOutput + Send + Sync + 'static,
+        Out: Encodable + Send + Sync + 'static,
+        S: Fn(Sec::Output) -> SFut + Send + Sync + 'static,
+        SFut: Future<Output = Result<P, Err::Error>> + Send + 'static,
+        P: Send + 'static,
+        H: Handler<HCons<P, In::Output>, Result<Out::Input, Err::Error>> + Send + Sync + 'static,
+        H::Future: Send + 'static,
+    {
+        self.secure_endpoint(endpoint, security_logic, move |principal, input| {
+            handler.call(HCons {
+                head: principal,
+                tail: input,
+            })
+        })
+    }
+
+    fn serve(mut self, endpoint: Arc<dyn ServerEndpoint>) -> Self {
+        Arc::make_mut(&mut self.endpoints).push(endpoint);
+        self
+    }
+
+    /// Wraps the service so every connection of a hyper server shares it:
+    /// `hyper::Server::bind(&address).serve(service.into_make_service())`.
+    pub fn into_make_service(self) -> Shared<Self> {
+        Shared::new(self)
+    }
+}
+
+/// Ranks templates matching the same path so that, like axum's router, a
+/// literal segment wins over a capture at the first position they differ,
+/// e.g. `/contacts/search` over `/contacts/{id}`.
+fn specificity(segments: &[String]) -> Vec<bool> {
+    segments
+        .iter()
+        .map(|segment| !segment.starts_with('{'))
+        .collect()
+}
+
+async fn dispatch(
+    endpoints: Arc<Vec<Arc<dyn ServerEndpoint>>>,
+    request: Request<Body>,
+) -> Response<Body> {
+    let path = request.uri().path().to_string();
+    let mut path_matched = false;
+    let mut best: Option<&Arc<dyn ServerEndpoint>> = None;
+    for endpoint in endpoints.iter() {
+        if !matches_template(endpoint.path_segments(), &path) {
+            continue;
+        }
+        path_matched = true;
+        if endpoint.method() != request.method() {
+            continue;
+        }
+        let more_specific = best.is_none_or(|best| {
+            specificity(endpoint.path_segments()) > specificity(best.path_segments())
+        });
+        if more_specific {
+            best = Some(endpoint);
+        }
+    }
+    match best {
+        Some(endpoint) => endpoint.handle(request).await,
+        None if path_matched => {
+            failure_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed")
+        }
+        None => failure_response(StatusCode::NOT_FOUND, "not found"),
+    }
+}
+
+impl Service<Request<Body>> for EndpointService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response<Body>, Infallible>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let endpoints = self.endpoints.clone();
+        Box::pin(async move { Ok(dispatch(endpoints, request).await) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use frunk::{hlist, HList, HNil};
+    use hyper::body::Bytes;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::codec::Text;
+    use crate::endpoint::endpoint;
+    use crate::extract::empty_endpoint;
+    use crate::output::{empty_output, response_body, BodyOutput};
+    use crate::path::{path_capture, path_literal, PathCapture, PathLiteral};
+
+    static CONTACTS: PathLiteral = path_literal("contacts");
+
+    static ID: PathCapture<u64> = path_capture("id");
+
+    static NAME: BodyOutput<Text> = response_body();
+
+    async fn get_contact(input: HList![u64]) -> Result<HList![String], Infallible> {
+        Ok(hlist![format!("contact {}", input.head)])
+    }
+
+    fn service() -> EndpointService {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        EndpointService::new().endpoint(endpoint(Method::GET, inputs).output(outputs), get_contact)
+    }
+
+    async fn send(service: EndpointService, method: Method, uri: &str) -> (StatusCode, Bytes) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        let status = response.status();
+        (
+            status,
+            hyper::body::to_bytes(response.into_body()).await.unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn requests_are_dispatched_by_path_and_method() {
+        let (status, body) = send(service(), Method::GET, "/contacts/7").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "contact 7");
+        let (status, _) = send(service(), Method::GET, "/contacts").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(service(), Method::DELETE, "/contacts/7").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    async fn get_contact_by_id(id: u64) -> Result<HList![String], Infallible> {
+        Ok(hlist![format!("contact {}", id)])
+    }
+
+    #[tokio::test]
+    async fn handlers_are_served_like_endpoints() {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        let service = EndpointService::new().handler(
+            endpoint(Method::GET, inputs).output(outputs),
+            get_contact_by_id,
+        );
+        let (status, body) = send(service, Method::GET, "/contacts/7").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "contact 7");
+    }
+
+    static SEARCH: PathLiteral = path_literal("search");
+
+    async fn search_contacts(_: HNil) -> Result<HList![String], Infallible> {
+        Ok(hlist!["search".to_string()])
+    }
+
+    #[tokio::test]
+    async fn literal_segments_win_over_captures() {
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&SEARCH);
+        let outputs = empty_output().with_encoder(&NAME);
+        let service = service().endpoint(
+            endpoint(Method::GET, inputs).output(outputs),
+            search_contacts,
+        );
+
+        let (status, body) = send(service.clone(), Method::GET, "/contacts/search").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "search");
+        let (status, body) = send(service, Method::GET, "/contacts/7").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "contact 7");
+    }
+
+    #[tokio::test]
+    async fn endpoints_can_be_added_after_cloning() {
+        let service = service();
+        let inputs = empty_endpoint()
+            .with_extractor(&CONTACTS)
+            .with_extractor(&ID);
+        let outputs = empty_output().with_encoder(&NAME);
+        let extended = service.clone().endpoint(
+            endpoint(Method::DELETE, inputs).output(outputs),
+            get_contact,
+        );
+
+        let (status, _) = send(extended, Method::DELETE, "/contacts/7").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(service, Method::DELETE, "/contacts/7").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
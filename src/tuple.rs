@@ -0,0 +1,77 @@
+use frunk::hlist::HNil;
+use frunk::{hlist, hlist_pat, HList};
+
+/// Conversions between an HList and the plain tuple of the same values, in
+/// the same order, e.g. `HList![String, u64]` and `(String, u64)`.
+/// Implemented for HLists of up to 16 values.
+pub trait HListTuple: Sized {
+    type Tuple;
+
+    fn into_tuple(self) -> Self::Tuple;
+
+    fn from_tuple(tuple: Self::Tuple) -> Self;
+}
+
+macro_rules! hlist_tuple {
+    ($($value:ident),*) => {
+        impl<$($value),*> HListTuple for HList![$($value),*] {
+            type Tuple = ($($value,)*);
+
+            #[allow(non_snake_case)]
+            fn into_tuple(self) -> Self::Tuple {
+                let hlist_pat![$($value),*] = self;
+                ($($value,)*)
+            }
+
+            #[allow(non_snake_case)]
+            fn from_tuple(tuple: Self::Tuple) -> Self {
+                let ($($value,)*) = tuple;
+                hlist![$($value),*]
+            }
+        }
+    };
+}
+
+impl HListTuple for HNil {
+    type Tuple = ();
+
+    fn into_tuple(self) {}
+
+    fn from_tuple((): ()) -> Self {
+        HNil
+    }
+}
+
+hlist_tuple!(A);
+hlist_tuple!(A, B);
+hlist_tuple!(A, B, C);
+hlist_tuple!(A, B, C, D);
+hlist_tuple!(A, B, C, D, E);
+hlist_tuple!(A, B, C, D, E, F);
+hlist_tuple!(A, B, C, D, E, F, G);
+hlist_tuple!(A, B, C, D, E, F, G, H);
+hlist_tuple!(A, B, C, D, E, F, G, H, I);
+hlist_tuple!(A, B, C, D, E, F, G, H, I, J);
+hlist_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+hlist_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+hlist_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+hlist_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+hlist_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+hlist_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hlists_and_tuples_convert_both_ways_in_order() {
+        let tuple = hlist![(), 7u64, "Jane".to_string()].into_tuple();
+        assert_eq!(tuple, ((), 7, "Jane".to_string()));
+        assert_eq!(
+            <HList![u64, String]>::from_tuple((7, "Jane".to_string())),
+            hlist![7, "Jane".to_string()]
+        );
+        HNil.into_tuple();
+        assert_eq!(HNil::from_tuple(()), HNil);
+    }
+}
@@ -0,0 +1,520 @@
+use std::sync::OnceLock;
+
+use async_trait::async_trait;
+use axum::http::request::Parts;
+use hyper::body::Bytes;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::decode::{DecodeFailure, DecodeInput, DecodeReason};
+use crate::encode::EncodeFailure;
+use crate::extract::Extractor;
+use crate::openapi::{Components, Operation};
+
+/// A declarative rule an input must satisfy once decoded.
+#[derive(Debug, Clone, Copy)]
+pub enum Validator {
+    /// Numbers must be at least this value.
+    Minimum(f64),
+    /// Numbers must be at most this value.
+    Maximum(f64),
+    /// Strings must have at least this many characters.
+    MinLength(usize),
+    /// Strings must have at most this many characters.
+    MaxLength(usize),
+    /// Strings must match this regular expression.
+    Pattern(&'static str),
+    /// Values must be one of these, compared in their text form.
+    OneOf(&'static [&'static str]),
+    /// Collections must have at least this many items.
+    MinItems(usize),
+    /// Collections must have at most this many items.
+    MaxItems(usize),
+}
+
+/// Values that validators can check. Rules that do not apply to a value,
+/// such as a pattern on a number, are rejected when the endpoint is built.
+pub trait Validate {
+    /// Whether `validator` applies to values of this type.
+    fn supports(validator: &Validator) -> bool;
+
+    /// Checks `validator`, returning a description of the broken rule. The
+    /// regular expression of a [`Validator::Pattern`] comes compiled as
+    /// `pattern`.
+    fn validate(&self, validator: &Validator, pattern: Option<&Regex>) -> Result<(), String>;
+}
+
+macro_rules! validate_number {
+    ($($number:ty),*) => {
+        $(
+            impl Validate for $number {
+                fn supports(validator: &Validator) -> bool {
+                    matches!(
+                        validator,
+                        Validator::Minimum(_) | Validator::Maximum(_) | Validator::OneOf(_)
+                    )
+                }
+
+                #[allow(clippy::unnecessary_cast)]
+                fn validate(&self, validator: &Validator, _: Option<&Regex>) -> Result<(), String> {
+                    let number = *self as f64;
+                    match *validator {
+                        Validator::Minimum(minimum) if number < minimum => {
+                            Err(format!("{} is less than {}", self, minimum))
+                        }
+                        Validator::Maximum(maximum) if number > maximum => {
+                            Err(format!("{} is greater than {}", self, maximum))
+                        }
+                        Validator::OneOf(allowed) => one_of(&self.to_string(), allowed),
+                        _ => Ok(()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+validate_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+fn one_of(value: &str, allowed: &[&str]) -> Result<(), String> {
+    if allowed.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{}` is not one of {}", value, allowed.join(", ")))
+    }
+}
+
+impl Validate for String {
+    fn supports(validator: &Validator) -> bool {
+        matches!(
+            validator,
+            Validator::MinLength(_)
+                | Validator::MaxLength(_)
+                | Validator::Pattern(_)
+                | Validator::OneOf(_)
+        )
+    }
+
+    fn validate(&self, validator: &Validator, pattern: Option<&Regex>) -> Result<(), String> {
+        let length = self.chars().count();
+        match *validator {
+            Validator::MinLength(minimum) if length < minimum => {
+                Err(format!("has {} characters, fewer than {}", length, minimum))
+            }
+            Validator::MaxLength(maximum) if length > maximum => {
+                Err(format!("has {} characters, more than {}", length, maximum))
+            }
+            Validator::Pattern(source) => match pattern {
+                Some(pattern) if pattern.is_match(self) => Ok(()),
+                _ => Err(format!("`{}` does not match `{}`", self, source)),
+            },
+            Validator::OneOf(allowed) => one_of(self, allowed),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Absent values are valid; present ones are checked.
+impl<T: Validate> Validate for Option<T> {
+    fn supports(validator: &Validator) -> bool {
+        T::supports(validator)
+    }
+
+    fn validate(&self, validator: &Validator, pattern: Option<&Regex>) -> Result<(), String> {
+        match self {
+            Some(value) => value.validate(validator, pattern),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Item counts are checked on the collection; all other rules on each item.
+impl<T: Validate> Validate for Vec<T> {
+    fn supports(validator: &Validator) -> bool {
+        match validator {
+            Validator::MinItems(_) | Validator::MaxItems(_) => true,
+            _ => T::supports(validator),
+        }
+    }
+
+    fn validate(&self, validator: &Validator, pattern: Option<&Regex>) -> Result<(), String> {
+        match *validator {
+            Validator::MinItems(minimum) if self.len() < minimum => {
+                Err(format!("has {} items, fewer than {}", self.len(), minimum))
+            }
+            Validator::MaxItems(maximum) if self.len() > maximum => {
+                Err(format!("has {} items, more than {}", self.len(), maximum))
+            }
+            Validator::MinItems(_) | Validator::MaxItems(_) => Ok(()),
+            _ => self
+                .iter()
+                .try_for_each(|item| item.validate(validator, pattern)),
+        }
+    }
+}
+
+impl Validator {
+    /// Compiles the regular expression of a [`Validator::Pattern`], panicking
+    /// if it is invalid.
+    pub fn compile(&self) -> Option<Regex> {
+        match *self {
+            Validator::Pattern(pattern) => match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(error) => panic!("invalid pattern `{}`: {}", pattern, error),
+            },
+            _ => None,
+        }
+    }
+
+    /// Exports this rule into a JSON Schema, e.g. as `minimum` or `pattern`.
+    /// Rules on the items of an array go into its `items`.
+    pub fn describe(&self, schema: &mut Value) {
+        let is_array = schema["type"] == "array";
+        let target = match self {
+            Validator::MinItems(_) | Validator::MaxItems(_) => schema,
+            _ if is_array => &mut schema["items"],
+            _ => schema,
+        };
+        if !target.is_object() {
+            return;
+        }
+        let (keyword, value) = match *self {
+            Validator::Minimum(minimum) => ("minimum", Value::from(minimum)),
+            Validator::Maximum(maximum) => ("maximum", Value::from(maximum)),
+            Validator::MinLength(minimum) => ("minLength", Value::from(minimum)),
+            Validator::MaxLength(maximum) => ("maxLength", Value::from(maximum)),
+            Validator::Pattern(pattern) => ("pattern", Value::from(pattern)),
+            Validator::OneOf(allowed) => {
+                // Allowed values of non-string types are written as JSON
+                // values, e.g. numbers.
+                let is_string = matches!(target["type"].as_str(), Some("string") | None);
+                let allowed = allowed
+                    .iter()
+                    .map(|value| {
+                        if is_string {
+                            Value::from(*value)
+                        } else {
+                            serde_json::from_str(value).unwrap_or_else(|_| Value::from(*value))
+                        }
+                    })
+                    .collect();
+                ("enum", Value::Array(allowed))
+            }
+            Validator::MinItems(minimum) => ("minItems", Value::from(minimum)),
+            Validator::MaxItems(maximum) => ("maxItems", Value::from(maximum)),
+        };
+        target[keyword] = value;
+    }
+}
+
+/// The validators of one value, e.g. of a [`Validated`] input or of a field
+/// declared with `#[validate(...)]`, with their patterns compiled once.
+#[derive(Clone)]
+pub struct Rules {
+    validators: &'static [Validator],
+    /// The regular expressions of the pattern validators, in order, compiled
+    /// when the endpoint is built.
+    patterns: OnceLock<Vec<Regex>>,
+}
+
+pub const fn rules(validators: &'static [Validator]) -> Rules {
+    Rules {
+        validators,
+        patterns: OnceLock::new(),
+    }
+}
+
+impl Rules {
+    fn patterns(&self) -> &[Regex] {
+        self.patterns.get_or_init(|| {
+            self.validators
+                .iter()
+                .filter_map(Validator::compile)
+                .collect()
+        })
+    }
+
+    /// Compiles the patterns, panicking if one is invalid or a validator
+    /// does not apply to values of type `T`.
+    pub fn compile<T: Validate>(&self) {
+        for validator in self.validators {
+            if !T::supports(validator) {
+                panic!(
+                    "validator `{:?}` does not apply to `{}`",
+                    validator,
+                    std::any::type_name::<T>()
+                );
+            }
+        }
+        self.patterns();
+    }
+
+    /// Checks every validator, returning the first broken rule.
+    pub fn check<T: Validate>(&self, value: &T) -> Result<(), String> {
+        let mut patterns = self.patterns().iter();
+        self.validators.iter().try_for_each(|validator| {
+            let pattern = match validator {
+                Validator::Pattern(_) => patterns.next(),
+                _ => None,
+            };
+            value.validate(validator, pattern)
+        })
+    }
+
+    /// Exports every validator into `schema`, see [`Validator::describe`].
+    pub fn describe(&self, schema: &mut Value) {
+        for validator in self.validators {
+            validator.describe(schema);
+        }
+    }
+}
+
+/// An input whose value is checked against `validators` right after it is
+/// decoded, e.g. `validated(query::<u8>("age"), &[Minimum(18.0), Maximum(120.0)])`.
+#[derive(Clone)]
+pub struct Validated<E> {
+    extractor: E,
+    rules: Rules,
+}
+
+pub const fn validated<E>(extractor: E, validators: &'static [Validator]) -> Validated<E> {
+    Validated {
+        extractor,
+        rules: rules(validators),
+    }
+}
+
+#[async_trait]
+impl<E> Extractor for Validated<E>
+where
+    E: Extractor,
+    E::Output: Validate,
+{
+    type Output = E::Output;
+
+    async fn extract(&self, request: &Parts, body: &Bytes) -> Result<E::Output, DecodeFailure> {
+        self.extract_at(0, request, body).await
+    }
+
+    async fn extract_at(
+        &self,
+        path_offset: usize,
+        request: &Parts,
+        body: &Bytes,
+    ) -> Result<E::Output, DecodeFailure> {
+        let value = self
+            .extractor
+            .extract_at(path_offset, request, body)
+            .await?;
+        self.rules.check(&value).map_err(|rule| {
+            DecodeFailure::new(
+                self.extractor.input(path_offset),
+                DecodeReason::Invalid(rule),
+            )
+        })?;
+        Ok(value)
+    }
+
+    fn path_segments(&self) -> Vec<String> {
+        Extractor::path_segments(&self.extractor)
+    }
+
+    fn input(&self, path_offset: usize) -> DecodeInput {
+        self.extractor.input(path_offset)
+    }
+
+    fn encode(
+        &self,
+        value: E::Output,
+        path_offset: usize,
+        request: &mut Parts,
+        body: &mut Bytes,
+    ) -> Result<(), EncodeFailure> {
+        self.rules
+            .check(&value)
+            .map_err(|rule| EncodeFailure::new(self.extractor.input(path_offset), rule))?;
+        Extractor::encode(&self.extractor, value, path_offset, request, body)
+    }
+
+    fn compile(&self) {
+        Extractor::compile(&self.extractor);
+        self.rules.compile::<E::Output>();
+    }
+
+    /// Adds the rules to the schemas the wrapped input documents.
+    fn describe(&self, operation: &mut Operation, components: &mut Components) {
+        let parameters = operation.parameters.len();
+        let had_body = operation.request_body.is_some();
+        Extractor::describe(&self.extractor, operation, components);
+        let mut schemas: Vec<&mut Value> = operation.parameters[parameters..]
+            .iter_mut()
+            .map(|parameter| &mut parameter.schema)
+            .collect();
+        if !had_body {
+            if let Some(body) = operation.request_body.as_mut() {
+                schemas.extend(
+                    body.content
+                        .values_mut()
+                        .map(|media_type| &mut media_type.schema),
+                );
+            }
+        }
+        for schema in schemas {
+            self.rules.describe(schema);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, Request};
+    use serde_json::json;
+
+    use super::*;
+    use crate::endpoint::endpoint;
+    use crate::extract::{empty_endpoint, Extractable};
+    use crate::query::{query, repeated_query};
+
+    fn parts(uri: &str) -> Parts {
+        Request::builder().uri(uri).body(()).unwrap().into_parts().0
+    }
+
+    /// Checks `value` against `pattern`, compiled like [`Validated`] does.
+    fn check_pattern<T: Validate>(value: &T, pattern: &'static str) -> Result<(), String> {
+        let validator = Validator::Pattern(pattern);
+        value.validate(&validator, validator.compile().as_ref())
+    }
+
+    #[test]
+    fn numbers_check_their_bounds_and_allowed_values() {
+        assert!(5u8.validate(&Validator::Minimum(1.0), None).is_ok());
+        assert_eq!(
+            0u8.validate(&Validator::Minimum(1.0), None),
+            Err("0 is less than 1".to_string())
+        );
+        assert!(1.5f64.validate(&Validator::Maximum(1.0), None).is_err());
+        assert!(3i32.validate(&Validator::OneOf(&["1", "3"]), None).is_ok());
+        assert!(2i32.validate(&Validator::OneOf(&["1", "3"]), None).is_err());
+        assert!(!u8::supports(&Validator::Pattern("^a$")));
+    }
+
+    #[test]
+    fn strings_check_length_pattern_and_allowed_values() {
+        let name = "Zoë".to_string();
+        assert!(name.validate(&Validator::MaxLength(3), None).is_ok());
+        assert!(name.validate(&Validator::MinLength(4), None).is_err());
+        assert!(check_pattern(&name, "^[A-Z]").is_ok());
+        assert_eq!(
+            check_pattern(&name, "^[a-z]+$"),
+            Err("`Zoë` does not match `^[a-z]+$`".to_string())
+        );
+        assert!(name
+            .validate(&Validator::OneOf(&["Zoë", "Ada"]), None)
+            .is_ok());
+        assert!(!String::supports(&Validator::Maximum(1.0)));
+    }
+
+    #[test]
+    fn collections_check_item_counts_and_each_item() {
+        let tags = vec!["a".to_string(), "B".to_string()];
+        assert!(tags.validate(&Validator::MaxItems(2), None).is_ok());
+        assert!(tags.validate(&Validator::MinItems(3), None).is_err());
+        assert!(check_pattern(&tags, "^[a-z]$").is_err());
+        assert!(None::<u8>.validate(&Validator::Minimum(1.0), None).is_ok());
+        assert!(Some(0u8).validate(&Validator::Minimum(1.0), None).is_err());
+    }
+
+    #[test]
+    fn rules_are_exported_into_schemas() {
+        let mut number = json!({ "type": "integer" });
+        Validator::Minimum(1.0).describe(&mut number);
+        Validator::OneOf(&["1", "2"]).describe(&mut number);
+        assert_eq!(
+            number,
+            json!({ "type": "integer", "minimum": 1.0, "enum": [1, 2] })
+        );
+
+        let mut tags = json!({ "type": "array", "items": { "type": "string" } });
+        Validator::MaxItems(10).describe(&mut tags);
+        Validator::Pattern("^[a-z]+$").describe(&mut tags);
+        Validator::OneOf(&["a", "b"]).describe(&mut tags);
+        assert_eq!(
+            tags,
+            json!({
+                "type": "array",
+                "items": { "type": "string", "pattern": "^[a-z]+$", "enum": ["a", "b"] },
+                "maxItems": 10,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn validated_inputs_reject_invalid_values() {
+        let page = validated(query::<u32>("page"), &[Validator::Minimum(1.0)]);
+        let request = parts("/contacts?page=2");
+        let extracted = Extractor::extract(&page, &request, &Bytes::new()).await;
+        assert_eq!(extracted.unwrap(), 2);
+
+        let request = parts("/contacts?page=0");
+        let failure = Extractor::extract(&page, &request, &Bytes::new())
+            .await
+            .unwrap_err();
+        assert_eq!(failure.input, DecodeInput::Query("page".to_string()));
+        assert_eq!(
+            failure.reason,
+            DecodeReason::Invalid("0 is less than 1".to_string())
+        );
+
+        let mut request = parts("/contacts");
+        let failure = Extractor::encode(&page, 0, 0, &mut request, &mut Bytes::new()).unwrap_err();
+        assert_eq!(failure.error, "0 is less than 1");
+    }
+
+    #[test]
+    fn validated_inputs_are_documented_with_their_rules() {
+        let tags = validated(
+            repeated_query::<String>("tags"),
+            &[Validator::MaxItems(2), Validator::MinLength(1)],
+        );
+        let mut operation = Operation::default();
+        Extractor::describe(&tags, &mut operation, &mut Components::default());
+        assert_eq!(
+            operation.parameters[0].schema,
+            json!({
+                "type": "array",
+                "items": { "type": "string", "minLength": 1 },
+                "maxItems": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn patterns_are_compiled_once_when_the_endpoint_is_built() {
+        let name = validated(query::<String>("name"), &[Validator::Pattern("^[a-z]+$")]);
+        endpoint(Method::GET, empty_endpoint().with_extractor(&name));
+        assert_eq!(name.rules.patterns.get().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn collections_support_item_counts_and_the_rules_of_their_items() {
+        assert!(Vec::<u32>::supports(&Validator::MaxItems(2)));
+        assert!(Vec::<u32>::supports(&Validator::Minimum(1.0)));
+        assert!(!Vec::<u32>::supports(&Validator::MinLength(1)));
+        assert!(Option::<String>::supports(&Validator::MinLength(1)));
+        assert!(!Option::<String>::supports(&Validator::MinItems(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "validator `MinLength(1)` does not apply to `u32`")]
+    fn rules_that_do_not_apply_fail_when_the_endpoint_is_built() {
+        let page = validated(query::<u32>("page"), &[Validator::MinLength(1)]);
+        endpoint(Method::GET, empty_endpoint().with_extractor(&page));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid pattern `[a-`")]
+    fn invalid_patterns_fail_when_the_endpoint_is_built() {
+        let name = validated(query::<String>("name"), &[Validator::Pattern("[a-")]);
+        endpoint(Method::GET, empty_endpoint().with_extractor(&name));
+    }
+}